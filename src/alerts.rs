@@ -0,0 +1,219 @@
+use core::fmt;
+
+use crate::decode::DecodedValue;
+use crate::models::Attribute;
+
+/// How far a sensor value is from its normal range, ordered from best to worst.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum Severity {
+    #[default]
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Normal => write!(f, "Normal"),
+            Severity::Warning => write!(f, "Warning"),
+            Severity::Critical => write!(f, "Critical"),
+        }
+    }
+}
+
+/// Conditions of one severity: a numeric range and values that trigger it.
+#[derive(Clone, PartialEq, Debug, Default)]
+struct Rule {
+    /// Numbers below this trigger the rule.
+    min: Option<f64>,
+    /// Numbers above this trigger the rule.
+    max: Option<f64>,
+    /// Values equal to one of these trigger the rule, e.g. `on` for a switch.
+    equals: Vec<String>,
+}
+
+impl Rule {
+    fn read(attributes: &[Attribute], prefix: &str) -> Rule {
+        let attribute = |suffix: &str| {
+            let key = format!("{prefix}_{suffix}");
+            attributes
+                .iter()
+                .find(|a| a.key.eq_ignore_ascii_case(&key))
+                .map(|a| a.value.trim())
+                .filter(|v| !v.is_empty())
+        };
+        let number = |suffix: &str| {
+            attribute(suffix)?
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+        };
+        Rule {
+            min: number("min"),
+            max: number("max"),
+            equals: attribute("eq")
+                .map(|v| v.split(',').map(|s| s.trim().to_string()).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.min.is_none() && self.max.is_none() && self.equals.is_empty()
+    }
+
+    fn matches(&self, value: &DecodedValue) -> bool {
+        match value {
+            DecodedValue::Number(n) => {
+                self.min.is_some_and(|min| *n < min)
+                    || self.max.is_some_and(|max| *n > max)
+                    || self
+                        .equals
+                        .iter()
+                        .any(|e| e.parse::<f64>().is_ok_and(|e| e == *n))
+            }
+            DecodedValue::Bool(b) => {
+                self.equals
+                    .iter()
+                    .any(|e| match e.to_ascii_lowercase().as_str() {
+                        "1" | "true" | "on" => *b,
+                        "0" | "false" | "off" => !*b,
+                        _ => false,
+                    })
+            }
+            DecodedValue::Text(text) | DecodedValue::Snapshot(text) => self
+                .equals
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(text.trim())),
+            DecodedValue::Multi(values) => values.iter().any(|v| self.matches(v)),
+        }
+    }
+}
+
+/// Threshold rules of a sensor, read from its attributes.
+///
+/// `warn_min`, `warn_max`, `crit_min` and `crit_max` bound the normal range of numbers, in the
+/// sensor's own unit. `warn_eq` and `crit_eq` list comma-separated values that raise the
+/// severity, for Text and Switch sensors.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ThresholdRules {
+    warning: Rule,
+    critical: Rule,
+}
+
+impl ThresholdRules {
+    pub fn new(attributes: &[Attribute]) -> Self {
+        ThresholdRules {
+            warning: Rule::read(attributes, "warn"),
+            critical: Rule::read(attributes, "crit"),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.warning.is_empty() && self.critical.is_empty()
+    }
+
+    pub fn severity(&self, value: &DecodedValue) -> Severity {
+        if self.critical.matches(value) {
+            Severity::Critical
+        } else if self.warning.matches(value) {
+            Severity::Warning
+        } else {
+            Severity::Normal
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(attributes: &[(&str, &str)]) -> ThresholdRules {
+        let attributes: Vec<Attribute> = attributes
+            .iter()
+            .map(|(key, value)| Attribute {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+            .collect();
+        ThresholdRules::new(&attributes)
+    }
+
+    #[test]
+    fn numbers_outside_the_range_raise_the_severity() {
+        let rules = rules(&[
+            ("warn_min", "10"),
+            ("warn_max", "30"),
+            ("crit_min", "0"),
+            ("CRIT_MAX", " 40 "),
+        ]);
+        let severity = |n: f64| rules.severity(&DecodedValue::Number(n));
+        assert_eq!(severity(20.0), Severity::Normal);
+        assert_eq!(severity(10.0), Severity::Normal);
+        assert_eq!(severity(5.0), Severity::Warning);
+        assert_eq!(severity(35.0), Severity::Warning);
+        assert_eq!(severity(-1.0), Severity::Critical);
+        assert_eq!(severity(41.0), Severity::Critical);
+    }
+
+    #[test]
+    fn critical_takes_precedence_over_warning() {
+        let value = DecodedValue::Number(5.0);
+        let warn_eq = rules(&[("warn_eq", "5"), ("crit_max", "4")]);
+        assert_eq!(warn_eq.severity(&value), Severity::Critical);
+        let crit_eq = rules(&[("warn_max", "4"), ("crit_eq", "5")]);
+        assert_eq!(crit_eq.severity(&value), Severity::Critical);
+    }
+
+    #[test]
+    fn equal_values_trigger_inside_the_range() {
+        let rules = rules(&[("warn_min", "0"), ("warn_max", "100"), ("warn_eq", "50")]);
+        assert_eq!(
+            rules.severity(&DecodedValue::Number(50.0)),
+            Severity::Warning
+        );
+        assert_eq!(
+            rules.severity(&DecodedValue::Number(51.0)),
+            Severity::Normal
+        );
+    }
+
+    #[test]
+    fn switch_and_text_values_match_any_spelling() {
+        let rules = rules(&[("crit_eq", "on"), ("warn_eq", "Idle, Fault")]);
+        assert_eq!(
+            rules.severity(&DecodedValue::Bool(true)),
+            Severity::Critical
+        );
+        assert_eq!(rules.severity(&DecodedValue::Bool(false)), Severity::Normal);
+        assert_eq!(
+            rules.severity(&DecodedValue::Text(" fault ".to_string())),
+            Severity::Warning
+        );
+        assert_eq!(
+            rules.severity(&DecodedValue::Text("Running".to_string())),
+            Severity::Normal
+        );
+    }
+
+    #[test]
+    fn any_of_several_values_triggers() {
+        let rules = rules(&[("warn_max", "10")]);
+        let values =
+            DecodedValue::Multi(vec![DecodedValue::Number(1.0), DecodedValue::Number(11.0)]);
+        assert_eq!(rules.severity(&values), Severity::Warning);
+    }
+
+    #[test]
+    fn blank_or_invalid_attributes_are_ignored() {
+        let rules = rules(&[("warn_min", ""), ("warn_max", "high"), ("crit_max", "inf")]);
+        assert!(rules.is_empty());
+        assert_eq!(rules.severity(&DecodedValue::Number(1e9)), Severity::Normal);
+    }
+
+    #[test]
+    fn severities_are_ordered() {
+        assert!(Severity::Normal < Severity::Warning);
+        assert!(Severity::Warning < Severity::Critical);
+    }
+}
@@ -0,0 +1,375 @@
+use core::fmt;
+
+use dioxus::prelude::*;
+use reqwest::{header::CONTENT_TYPE, Client, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::models::{
+    ActiveDevice, ActiveInfo, ActiveNotify, Device, EditActiveDevice, EditActiveNotify, EditDevice,
+    EditRawData, EditSensor, Endpoint, EndpointTrait, Project, RawData, RawDataQuery, Sensor,
+};
+
+/// Failure of a request made through [`ApiClient`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// The page has no project or the project's endpoint is missing.
+    NotConfigured,
+    /// The request did not get a response (DNS, connection refused, CORS, ...).
+    Network(String),
+    /// The server answered with a non-2xx status.
+    Status { status: StatusCode, body: String },
+    /// The response body was not the expected JSON.
+    Decode { message: String, body: String },
+}
+
+impl ApiError {
+    /// Short explanation of the failure, suitable for a panel heading.
+    pub fn summary(&self) -> String {
+        match self {
+            ApiError::NotConfigured => "Project or endpoint is not configured".to_string(),
+            ApiError::Network(_) => "Endpoint is unreachable".to_string(),
+            ApiError::Status { status, .. } => match status.as_u16() {
+                401 | 403 => format!("Rejected project key ({status})"),
+                404 => format!("Not found ({status})"),
+                500..=599 => format!("Endpoint error ({status})"),
+                _ => format!("Request failed ({status})"),
+            },
+            ApiError::Decode { .. } => "Unexpected response from endpoint".to_string(),
+        }
+    }
+
+    /// Detail text: the transport error, the server message or the decode error.
+    pub fn detail(&self) -> String {
+        match self {
+            ApiError::NotConfigured => String::new(),
+            ApiError::Network(message) => message.clone(),
+            ApiError::Status { body, .. } => body.clone(),
+            ApiError::Decode { message, .. } => message.clone(),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let detail = self.detail();
+        if detail.is_empty() {
+            write!(f, "{}", self.summary())
+        } else {
+            write!(f, "{}: {detail}", self.summary())
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        ApiError::Network(e.to_string())
+    }
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// Devices of a project, read one by one so a malformed entry does not hide the others.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Metadata {
+    pub devices: Vec<Device>,
+    /// One message per device or sensor that was skipped.
+    pub problems: Vec<String>,
+}
+
+/// Names an entry of a JSON list by its `id`, or by its position when it has none.
+fn entry_label(value: &Value, index: usize) -> String {
+    match value.get("id").and_then(Value::as_str) {
+        Some(id) => format!("'{id}'"),
+        None => format!("#{index}"),
+    }
+}
+
+impl Metadata {
+    fn from_values(values: Vec<Value>) -> Self {
+        let mut metadata = Metadata::default();
+        for (index, value) in values.into_iter().enumerate() {
+            if let Some(device) = metadata.parse_device(value, index) {
+                metadata.devices.push(device);
+            }
+        }
+        metadata
+    }
+
+    /// Parses a device; if that fails, parses it without sensors and keeps the readable ones.
+    fn parse_device(&mut self, mut value: Value, index: usize) -> Option<Device> {
+        if let Ok(device) = serde_json::from_value::<Device>(value.clone()) {
+            return Some(device);
+        }
+
+        let label = entry_label(&value, index);
+        let sensors = value.as_object_mut().and_then(|o| o.remove("sensors"));
+        let mut device = match serde_json::from_value::<Device>(value) {
+            Ok(device) => device,
+            Err(e) => {
+                self.problems.push(format!("Device {label} skipped: {e}"));
+                return None;
+            }
+        };
+
+        let sensors = match sensors {
+            None | Some(Value::Null) => return Some(device),
+            Some(Value::Array(sensors)) => sensors,
+            Some(_) => {
+                self.problems
+                    .push(format!("Device {label}: sensors skipped, not a list"));
+                Vec::new()
+            }
+        };
+        let mut parsed = Vec::new();
+        for (i, sensor) in sensors.into_iter().enumerate() {
+            let sensor_label = entry_label(&sensor, i);
+            match serde_json::from_value::<Sensor>(sensor) {
+                Ok(sensor) => parsed.push(sensor),
+                Err(e) => self.problems.push(format!(
+                    "Device {label}: sensor {sensor_label} skipped: {e}"
+                )),
+            }
+        }
+        device.sensors = Some(parsed);
+        Some(device)
+    }
+}
+
+/// The file of a snapshot and the type the server declared for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub bytes: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+/// Typed client for a single project on an endpoint.
+///
+/// All requests share the [`Client`] connection pool passed to [`ApiClient::new`] and carry the
+/// project key in the `CK` header.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    client: Client,
+    endpoint: Endpoint,
+    project_key: String,
+}
+
+impl PartialEq for ApiClient {
+    fn eq(&self, other: &Self) -> bool {
+        self.endpoint == other.endpoint && self.project_key == other.project_key
+    }
+}
+
+impl ApiClient {
+    pub fn new(client: Client, project: &Project, endpoint: Endpoint) -> Self {
+        Self {
+            client,
+            endpoint,
+            project_key: project.project_key.clone(),
+        }
+    }
+
+    fn get(&self, url: String) -> RequestBuilder {
+        self.client.get(url).header("CK", self.project_key.as_str())
+    }
+
+    fn put(&self, url: String) -> RequestBuilder {
+        self.client.put(url).header("CK", self.project_key.as_str())
+    }
+
+    fn post(&self, url: String) -> RequestBuilder {
+        self.client
+            .post(url)
+            .header("CK", self.project_key.as_str())
+    }
+
+    fn delete(&self, url: String) -> RequestBuilder {
+        self.client
+            .delete(url)
+            .header("CK", self.project_key.as_str())
+    }
+
+    /// Sends the request and returns the response if its status is 2xx.
+    async fn send(request: RequestBuilder) -> ApiResult<reqwest::Response> {
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(response)
+        } else {
+            let body = response.text().await.unwrap_or_default();
+            Err(ApiError::Status { status, body })
+        }
+    }
+
+    async fn text(request: RequestBuilder) -> ApiResult<String> {
+        Ok(Self::send(request).await?.text().await?)
+    }
+
+    async fn json<T: DeserializeOwned>(request: RequestBuilder) -> ApiResult<T> {
+        let body = Self::text(request).await?;
+        serde_json::from_str(&body).map_err(|e| ApiError::Decode {
+            message: e.to_string(),
+            body,
+        })
+    }
+
+    pub async fn metadata(&self) -> ApiResult<Metadata> {
+        let values: Vec<Value> = Self::json(self.get(self.endpoint.metadata())).await?;
+        Ok(Metadata::from_values(values))
+    }
+
+    pub async fn rawdata(&self, device_id: &str) -> ApiResult<Vec<RawData>> {
+        Self::json(self.get(self.endpoint.rawdata(device_id))).await
+    }
+
+    /// Historical raw data of one sensor within the query's time range.
+    pub async fn sensor_rawdata(
+        &self,
+        device_id: &str,
+        sensor_id: &str,
+        query: &RawDataQuery,
+    ) -> ApiResult<Vec<RawData>> {
+        let url = self.endpoint.sensor_rawdata(device_id, sensor_id, query);
+        Self::json(self.get(url)).await
+    }
+
+    /// Writes new values for one or more sensors of a device.
+    pub async fn write_rawdata(&self, device_id: &str, data: &[EditRawData]) -> ApiResult<String> {
+        Self::text(self.post(self.endpoint.write_rawdata(device_id)).json(data)).await
+    }
+
+    pub async fn snapshot(
+        &self,
+        device_id: &str,
+        sensor_id: &str,
+        snapshot_id: &str,
+    ) -> ApiResult<Snapshot> {
+        let url = self.endpoint.snapshot(device_id, sensor_id, snapshot_id);
+        let response = Self::send(self.get(url)).await?;
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let bytes = response.bytes().await?.to_vec();
+        Ok(Snapshot {
+            bytes,
+            content_type,
+        })
+    }
+
+    /// Uploads the file of a new snapshot. The snapshot is shown once a rawdata value refers
+    /// to it, see [`ApiClient::write_rawdata`].
+    pub async fn upload_snapshot(
+        &self,
+        device_id: &str,
+        sensor_id: &str,
+        snapshot_id: &str,
+        content_type: &str,
+        bytes: Vec<u8>,
+    ) -> ApiResult<String> {
+        let url = self
+            .endpoint
+            .upload_snapshot(device_id, sensor_id, snapshot_id);
+        Self::text(
+            self.post(url)
+                .header(CONTENT_TYPE, content_type)
+                .body(bytes),
+        )
+        .await
+    }
+
+    /// Creates a device; `device.id` must be set.
+    pub async fn create_device(&self, device: &EditDevice) -> ApiResult<String> {
+        Self::text(self.post(self.endpoint.create_device()).json(device)).await
+    }
+
+    pub async fn delete_device(&self, device_id: &str) -> ApiResult<String> {
+        Self::text(self.delete(self.endpoint.delete_device(device_id))).await
+    }
+
+    /// Updates a device and returns the server's response text.
+    pub async fn update_device(&self, device_id: &str, device: &EditDevice) -> ApiResult<String> {
+        Self::text(self.put(self.endpoint.device(device_id)).json(device)).await
+    }
+
+    /// Updates a sensor and returns the server's response text.
+    pub async fn update_sensor(
+        &self,
+        device_id: &str,
+        sensor_id: &str,
+        sensor: &EditSensor,
+    ) -> ApiResult<String> {
+        let url = self.endpoint.sensor(device_id, sensor_id);
+        Self::text(self.put(url).json(sensor)).await
+    }
+
+    /// Creates a sensor on a device; `sensor.id` must be set.
+    pub async fn create_sensor(&self, device_id: &str, sensor: &EditSensor) -> ApiResult<String> {
+        Self::text(
+            self.post(self.endpoint.create_sensor(device_id))
+                .json(sensor),
+        )
+        .await
+    }
+
+    pub async fn delete_sensor(&self, device_id: &str, sensor_id: &str) -> ApiResult<String> {
+        Self::text(self.delete(self.endpoint.delete_sensor(device_id, sensor_id))).await
+    }
+
+    pub async fn active(&self, device_id: &str) -> ApiResult<Option<ActiveInfo>> {
+        Self::json(self.get(self.endpoint.active(device_id))).await
+    }
+
+    pub async fn active_setting(&self, device_id: &str) -> ApiResult<ActiveDevice> {
+        Self::json(self.get(self.endpoint.active_setting(device_id))).await
+    }
+
+    /// Updates the active monitor setting of a device.
+    pub async fn update_active_setting(
+        &self,
+        device_id: &str,
+        setting: &EditActiveDevice,
+    ) -> ApiResult<String> {
+        let url = self.endpoint.active_setting(device_id);
+        Self::text(self.put(url).json(setting)).await
+    }
+
+    pub async fn active_notify(&self, device_id: &str) -> ApiResult<Vec<ActiveNotify>> {
+        Self::json(self.get(self.endpoint.active_notify(device_id))).await
+    }
+
+    pub async fn create_active_notify(
+        &self,
+        device_id: &str,
+        notify: &EditActiveNotify,
+    ) -> ApiResult<String> {
+        Self::text(
+            self.post(self.endpoint.active_notify(device_id))
+                .json(notify),
+        )
+        .await
+    }
+
+    pub async fn update_active_notify(
+        &self,
+        device_id: &str,
+        notify_id: i32,
+        notify: &EditActiveNotify,
+    ) -> ApiResult<String> {
+        let url = format!("{}/{notify_id}", self.endpoint.active_notify(device_id));
+        Self::text(self.put(url).json(notify)).await
+    }
+
+    pub async fn delete_active_notify(&self, device_id: &str, notify_id: i32) -> ApiResult<String> {
+        let url = format!("{}/{notify_id}", self.endpoint.active_notify(device_id));
+        Self::text(self.delete(url)).await
+    }
+}
+
+/// Returns the [`ApiClient`] of the current project page, if the project and its endpoint exist.
+pub fn use_api() -> Memo<Option<ApiClient>> {
+    use_context::<Memo<Option<ApiClient>>>()
+}
@@ -0,0 +1,372 @@
+use chrono::{DateTime, Utc};
+use dioxus::prelude::*;
+
+use crate::components::button::{Button, ButtonVariant};
+use crate::timestamp::{TimeZoneChoice, Timestamp};
+
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 240.0;
+const MARGIN_LEFT: f64 = 56.0;
+const MARGIN_RIGHT: f64 = 16.0;
+const MARGIN_TOP: f64 = 12.0;
+const MARGIN_BOTTOM: f64 = 28.0;
+const TOOLTIP_WIDTH: f64 = 150.0;
+const TOOLTIP_HEIGHT: f64 = 38.0;
+
+/// Candidate spacings for the time axis, in milliseconds.
+const TIME_STEPS: [f64; 20] = [
+    1e3, 2e3, 5e3, 1e4, 1.5e4, 3e4, 6e4, 1.2e5, 3e5, 6e5, 9e5, 1.8e6, 3.6e6, 7.2e6, 1.08e7, 2.16e7,
+    4.32e7, 8.64e7, 1.728e8, 6.048e8,
+];
+
+/// One sample of a time series.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartPoint {
+    pub time: DateTime<Utc>,
+    pub value: f64,
+}
+
+impl ChartPoint {
+    fn x(&self) -> f64 {
+        self.time.timestamp_millis() as f64
+    }
+}
+
+/// Rounds `raw` up to 1, 2 or 5 times a power of ten.
+fn nice_step(raw: f64) -> f64 {
+    if raw <= 0.0 || !raw.is_finite() {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let residual = raw / magnitude;
+    let nice = if residual <= 1.0 {
+        1.0
+    } else if residual <= 2.0 {
+        2.0
+    } else if residual <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+fn ticks(min: f64, max: f64, step: f64) -> Vec<f64> {
+    let mut tick = (min / step).ceil() * step;
+    let mut out = Vec::new();
+    while tick <= max + step * 1e-9 {
+        out.push(tick);
+        tick += step;
+    }
+    out
+}
+
+fn time_step(range: f64) -> f64 {
+    let raw = range / 5.0;
+    TIME_STEPS
+        .iter()
+        .copied()
+        .find(|step| *step >= raw)
+        .unwrap_or_else(|| (raw / 8.64e7).ceil() * 8.64e7)
+}
+
+fn format_time(x: f64, step: f64, zone: TimeZoneChoice) -> String {
+    let Some(time) = DateTime::from_timestamp_millis(x as i64) else {
+        return String::new();
+    };
+    let format = if step >= 8.64e7 {
+        "%m-%d"
+    } else if step >= 6e4 {
+        "%H:%M"
+    } else {
+        "%H:%M:%S"
+    };
+    Timestamp(time).display(zone, format)
+}
+
+fn format_value(value: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).clamp(0.0, 6.0) as usize;
+    format!("{value:.decimals$}")
+}
+
+/// Time-series line chart with axes, hover tooltip, drag-to-zoom and wheel zoom.
+///
+/// `min` and `max` fix the ends of the value axis, which still grows to fit points outside
+/// them. `precision` sets the decimals of the tooltip value.
+#[component]
+pub fn LineChart(
+    points: Vec<ChartPoint>,
+    #[props(default)] unit: String,
+    #[props(default)] precision: Option<usize>,
+    #[props(default)] min: Option<f64>,
+    #[props(default)] max: Option<f64>,
+    #[props(default)] time_zone: TimeZoneChoice,
+) -> Element {
+    let mut zoom = use_signal(|| None::<(f64, f64)>);
+    let mut hovered = use_signal(|| None::<usize>);
+    let mut drag = use_signal(|| None::<(f64, f64)>);
+
+    let mut sorted: Vec<ChartPoint> = points
+        .iter()
+        .copied()
+        .filter(|p| p.value.is_finite())
+        .collect();
+    sorted.sort_by_key(|p| p.time);
+    let full = match (sorted.first(), sorted.last()) {
+        (Some(first), Some(last)) => (first.x(), last.x()),
+        _ => (0.0, 0.0),
+    };
+    let visible: Vec<ChartPoint> = match zoom() {
+        Some((from, to)) => sorted
+            .into_iter()
+            .filter(|p| p.x() >= from && p.x() <= to)
+            .collect(),
+        None => sorted,
+    };
+
+    let reset_zoom = move |_| {
+        zoom.set(None);
+        hovered.set(None);
+    };
+    let toolbar = rsx! {
+        div { class: "line-chart-toolbar",
+            span { "Drag or scroll to zoom" }
+            if zoom().is_some() {
+                Button { variant: ButtonVariant::Ghost, onclick: reset_zoom, "Reset zoom" }
+            }
+        }
+    };
+
+    if visible.len() < 2 {
+        return rsx! {
+            document::Link { rel: "stylesheet", href: asset!("./style.css") }
+            div { class: "line-chart",
+                {toolbar}
+                p { class: "line-chart-empty", "Not enough numeric data to plot" }
+            }
+        };
+    }
+
+    let x_min = visible[0].x();
+    let x_max = visible[visible.len() - 1].x().max(x_min + 1000.0);
+    let (mut y_min, mut y_max) = visible
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+            (lo.min(p.value), hi.max(p.value))
+        });
+    if y_max <= y_min {
+        y_min -= 1.0;
+        y_max += 1.0;
+    } else {
+        let pad = (y_max - y_min) * 0.05;
+        y_min -= pad;
+        y_max += pad;
+    }
+    if let Some(min) = min {
+        y_min = y_min.min(min);
+    }
+    if let Some(max) = max {
+        y_max = y_max.max(max);
+    }
+
+    let plot_w = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_h = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let sx = move |x: f64| MARGIN_LEFT + (x - x_min) / (x_max - x_min) * plot_w;
+    let sy = move |y: f64| MARGIN_TOP + (y_max - y) / (y_max - y_min) * plot_h;
+
+    let y_step = nice_step((y_max - y_min) / 4.0);
+    let y_ticks = ticks(y_min, y_max, y_step);
+    let x_step = time_step(x_max - x_min);
+    let x_ticks = ticks(x_min, x_max, x_step);
+
+    let path = visible
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let cmd = if i == 0 { "M" } else { "L" };
+            format!("{cmd}{:.1},{:.1}", sx(p.x()), sy(p.value))
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    // Each point owns the horizontal band closest to it, so hovering anywhere picks a point.
+    let bands: Vec<(f64, f64, f64)> = visible
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let left = match i {
+                0 => MARGIN_LEFT,
+                _ => (sx(visible[i - 1].x()) + sx(p.x())) / 2.0,
+            };
+            let right = match visible.get(i + 1) {
+                Some(next) => (sx(p.x()) + sx(next.x())) / 2.0,
+                None => WIDTH - MARGIN_RIGHT,
+            };
+            (p.x(), left, right)
+        })
+        .collect();
+
+    let selection = drag().filter(|(a, b)| a != b).map(|(a, b)| {
+        let left = sx(a.min(b));
+        (left, sx(a.max(b)) - left)
+    });
+
+    let tooltip = hovered().and_then(|i| visible.get(i)).map(|p| {
+        let px = sx(p.x());
+        let py = sy(p.value);
+        let bx = if px + 12.0 + TOOLTIP_WIDTH > WIDTH - MARGIN_RIGHT {
+            px - 12.0 - TOOLTIP_WIDTH
+        } else {
+            px + 12.0
+        };
+        let by =
+            (py - TOOLTIP_HEIGHT / 2.0).clamp(MARGIN_TOP, MARGIN_TOP + plot_h - TOOLTIP_HEIGHT);
+        let value = match precision {
+            Some(decimals) => format!("{:.decimals$} {unit}", p.value),
+            None => format!("{} {unit}", format_value(p.value, y_step / 10.0)),
+        };
+        let time = Timestamp(p.time).display(time_zone, "%Y-%m-%d %H:%M:%S");
+        (px, py, bx, by, value, time)
+    });
+
+    let on_mouseup = move |_| {
+        if let Some((a, b)) = drag() {
+            if a != b {
+                zoom.set(Some((a.min(b), a.max(b))));
+                hovered.set(None);
+            }
+        }
+        drag.set(None);
+    };
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: asset!("./style.css") }
+        div { class: "line-chart",
+            {toolbar}
+            svg {
+                class: "line-chart-svg",
+                view_box: "0 0 {WIDTH} {HEIGHT}",
+                xmlns: "http://www.w3.org/2000/svg",
+                onmouseup: on_mouseup,
+                onmouseleave: move |_| {
+                    hovered.set(None);
+                    drag.set(None);
+                },
+
+                for y in y_ticks {
+                    line {
+                        class: "line-chart-grid",
+                        x1: "{MARGIN_LEFT}",
+                        x2: "{WIDTH - MARGIN_RIGHT}",
+                        y1: "{sy(y)}",
+                        y2: "{sy(y)}",
+                    }
+                    text {
+                        class: "line-chart-tick",
+                        x: "{MARGIN_LEFT - 6.0}",
+                        y: "{sy(y) + 4.0}",
+                        text_anchor: "end",
+                        {format_value(y, y_step)}
+                    }
+                }
+                for x in x_ticks {
+                    line {
+                        class: "line-chart-axis",
+                        x1: "{sx(x)}",
+                        x2: "{sx(x)}",
+                        y1: "{MARGIN_TOP + plot_h}",
+                        y2: "{MARGIN_TOP + plot_h + 4.0}",
+                    }
+                    text {
+                        class: "line-chart-tick",
+                        x: "{sx(x)}",
+                        y: "{HEIGHT - 8.0}",
+                        text_anchor: "middle",
+                        {format_time(x, x_step, time_zone)}
+                    }
+                }
+                line {
+                    class: "line-chart-axis",
+                    x1: "{MARGIN_LEFT}",
+                    x2: "{MARGIN_LEFT}",
+                    y1: "{MARGIN_TOP}",
+                    y2: "{MARGIN_TOP + plot_h}",
+                }
+                line {
+                    class: "line-chart-axis",
+                    x1: "{MARGIN_LEFT}",
+                    x2: "{WIDTH - MARGIN_RIGHT}",
+                    y1: "{MARGIN_TOP + plot_h}",
+                    y2: "{MARGIN_TOP + plot_h}",
+                }
+
+                path { class: "line-chart-line", d: "{path}" }
+
+                if let Some((left, width)) = selection {
+                    rect {
+                        class: "line-chart-selection",
+                        x: "{left}",
+                        y: "{MARGIN_TOP}",
+                        width: "{width}",
+                        height: "{plot_h}",
+                    }
+                }
+
+                if let Some((px, py, bx, by, value, time)) = tooltip {
+                    line {
+                        class: "line-chart-cursor",
+                        x1: "{px}",
+                        x2: "{px}",
+                        y1: "{MARGIN_TOP}",
+                        y2: "{MARGIN_TOP + plot_h}",
+                    }
+                    circle { class: "line-chart-point", cx: "{px}", cy: "{py}", r: "4" }
+                    g { class: "line-chart-tooltip",
+                        rect {
+                            x: "{bx}",
+                            y: "{by}",
+                            width: "{TOOLTIP_WIDTH}",
+                            height: "{TOOLTIP_HEIGHT}",
+                            rx: "4",
+                        }
+                        text { x: "{bx + 8.0}", y: "{by + 15.0}", {value} }
+                        text { x: "{bx + 8.0}", y: "{by + 30.0}", {time} }
+                    }
+                }
+
+                for (i , (x , left , right)) in bands.into_iter().enumerate() {
+                    rect {
+                        key: "{i}",
+                        x: "{left}",
+                        y: "{MARGIN_TOP}",
+                        width: "{right - left}",
+                        height: "{plot_h}",
+                        fill: "transparent",
+                        onmouseenter: move |_| {
+                            hovered.set(Some(i));
+                            if let Some((start, _)) = drag() {
+                                drag.set(Some((start, x)));
+                            }
+                        },
+                        onmousedown: move |e| {
+                            e.prevent_default();
+                            drag.set(Some((x, x)));
+                        },
+                        onwheel: move |e| {
+                            e.prevent_default();
+                            let factor = if e.delta().strip_units().y < 0.0 { 0.5 } else { 2.0 };
+                            let (lo, hi) = zoom().unwrap_or(full);
+                            let window = (x - (x - lo) * factor, x + (hi - x) * factor);
+                            hovered.set(None);
+                            if window.0 <= full.0 && window.1 >= full.1 {
+                                zoom.set(None);
+                            } else {
+                                zoom.set(Some(window));
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
@@ -20,3 +20,5 @@ pub mod toast;
 pub mod select;
 pub mod radio_group;
 pub mod textarea;
+pub mod line_chart;
+pub mod switch;
\ No newline at end of file
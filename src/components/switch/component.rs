@@ -0,0 +1,21 @@
+use dioxus::prelude::*;
+use dioxus_primitives::switch::{self, SwitchProps};
+
+#[component]
+pub fn Switch(props: SwitchProps) -> Element {
+    rsx! {
+        document::Link { rel: "stylesheet", href: asset!("./style.css") }
+        switch::Switch {
+            class: "switch",
+            checked: props.checked,
+            default_checked: props.default_checked,
+            disabled: props.disabled,
+            required: props.required,
+            name: props.name,
+            value: props.value,
+            on_checked_change: props.on_checked_change,
+            attributes: props.attributes,
+            switch::SwitchThumb { class: "switch-thumb" }
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use core::fmt;
+
+use serde::{Serialize, Serializer};
+
+use crate::formula::apply_formula;
+use crate::models::{Sensor, SensorType};
+
+/// Prefix of the raw value of a snapshot sensor, followed by the snapshot id.
+const SNAPSHOT_PREFIX: &str = "snapshot://";
+
+/// A raw sensor value decoded according to its [`SensorType`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedValue {
+    Number(f64),
+    Bool(bool),
+    Text(String),
+    /// Id of a snapshot to fetch with `ApiClient::snapshot`.
+    Snapshot(String),
+    /// A sensor reporting more than one value at a time.
+    Multi(Vec<DecodedValue>),
+}
+
+impl DecodedValue {
+    /// The value to chart: a number, or the first number of a multi-value.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            DecodedValue::Number(n) => Some(*n),
+            DecodedValue::Multi(values) => values.first()?.as_number(),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            DecodedValue::Bool(b) => Some(*b),
+            DecodedValue::Multi(values) => values.first()?.as_bool(),
+            _ => None,
+        }
+    }
+
+    pub fn snapshot_id(&self) -> Option<&str> {
+        match self {
+            DecodedValue::Snapshot(id) => Some(id),
+            DecodedValue::Multi(values) => values.first()?.snapshot_id(),
+            _ => None,
+        }
+    }
+
+    /// Encodes the value back into raw values that [`decode_values`] reads the same way.
+    pub fn encode(&self) -> Vec<String> {
+        match self {
+            DecodedValue::Number(n) => vec![n.to_string()],
+            DecodedValue::Bool(b) => vec![if *b { "1" } else { "0" }.to_string()],
+            DecodedValue::Text(text) => vec![text.clone()],
+            DecodedValue::Snapshot(id) => vec![format!("{SNAPSHOT_PREFIX}{id}")],
+            DecodedValue::Multi(values) => values.iter().flat_map(DecodedValue::encode).collect(),
+        }
+    }
+}
+
+impl fmt::Display for DecodedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodedValue::Number(n) => write!(f, "{n}"),
+            DecodedValue::Bool(true) => write!(f, "On"),
+            DecodedValue::Bool(false) => write!(f, "Off"),
+            DecodedValue::Text(text) => write!(f, "{text}"),
+            DecodedValue::Snapshot(id) => write!(f, "{SNAPSHOT_PREFIX}{id}"),
+            DecodedValue::Multi(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Numbers, switch states and text serialize as their JSON counterparts, a snapshot as its raw
+/// reference and a multi-value as an array.
+impl Serialize for DecodedValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            DecodedValue::Number(n) => serializer.serialize_f64(*n),
+            DecodedValue::Bool(b) => serializer.serialize_bool(*b),
+            DecodedValue::Text(text) => serializer.serialize_str(text),
+            DecodedValue::Snapshot(_) => serializer.collect_str(self),
+            DecodedValue::Multi(values) => serializer.collect_seq(values),
+        }
+    }
+}
+
+/// Raw values that do not match the sensor's type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeError {
+    pub message: String,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+fn decode_one(kind: &SensorType, raw: &str) -> Result<DecodedValue, DecodeError> {
+    let trimmed = raw.trim();
+    let error = |expected: &str| DecodeError {
+        message: format!("'{raw}' is not {expected}"),
+    };
+    match kind {
+        SensorType::Gauge => trimmed
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(DecodedValue::Number)
+            .ok_or_else(|| error("a number")),
+        SensorType::Switch => match trimmed.to_ascii_lowercase().as_str() {
+            "1" | "true" | "on" => Ok(DecodedValue::Bool(true)),
+            "0" | "false" | "off" => Ok(DecodedValue::Bool(false)),
+            _ => Err(error("a switch state (1/0, true/false or on/off)")),
+        },
+        SensorType::Snapshot => trimmed
+            .strip_prefix(SNAPSHOT_PREFIX)
+            .filter(|id| !id.is_empty())
+            .map(|id| DecodedValue::Snapshot(id.to_string()))
+            .ok_or_else(|| error(&format!("a snapshot reference ({SNAPSHOT_PREFIX}<id>)"))),
+        SensorType::Text | SensorType::Unknown(_) => Ok(DecodedValue::Text(raw.to_string())),
+    }
+}
+
+/// Decodes the raw values of a sensor of the given type.
+///
+/// A single value decodes to a scalar, several values to [`DecodedValue::Multi`]. Values of
+/// unknown types are kept as text.
+pub fn decode_values(kind: &SensorType, values: &[String]) -> Result<DecodedValue, DecodeError> {
+    match values {
+        [] => Err(DecodeError {
+            message: "No value".to_string(),
+        }),
+        [value] => decode_one(kind, value),
+        values => values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                decode_one(kind, v).map_err(|e| DecodeError {
+                    message: format!("value[{i}]: {}", e.message),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(DecodedValue::Multi),
+    }
+}
+
+/// Applies the formula of a gauge sensor, then decodes the result according to the type.
+pub fn decode_sensor(sensor: &Sensor, values: &[String]) -> Result<DecodedValue, String> {
+    let formula = sensor
+        .formula
+        .as_deref()
+        .filter(|_| sensor.kind.has_formula());
+    let values = apply_formula(formula, values).map_err(|e| format!("Formula error: {e}"))?;
+    decode_values(&sensor.kind, &values).map_err(|e| format!("Invalid {} value: {e}", sensor.kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn gauge_values_are_numbers() {
+        assert_eq!(
+            decode_values(&SensorType::Gauge, &values(&[" 21.5 "])),
+            Ok(DecodedValue::Number(21.5))
+        );
+        assert!(decode_values(&SensorType::Gauge, &values(&["warm"])).is_err());
+        assert!(decode_values(&SensorType::Gauge, &values(&["NaN"])).is_err());
+    }
+
+    #[test]
+    fn switch_values_accept_each_spelling() {
+        for (raw, on) in [
+            ("1", true),
+            ("TRUE", true),
+            ("on", true),
+            ("0", false),
+            ("Off", false),
+        ] {
+            assert_eq!(
+                decode_values(&SensorType::Switch, &values(&[raw])),
+                Ok(DecodedValue::Bool(on))
+            );
+        }
+        assert!(decode_values(&SensorType::Switch, &values(&["2"])).is_err());
+    }
+
+    #[test]
+    fn snapshot_values_need_the_prefix_and_an_id() {
+        assert_eq!(
+            decode_values(&SensorType::Snapshot, &values(&["snapshot://abc"])),
+            Ok(DecodedValue::Snapshot("abc".to_string()))
+        );
+        assert!(decode_values(&SensorType::Snapshot, &values(&["snapshot://"])).is_err());
+        assert!(decode_values(&SensorType::Snapshot, &values(&["abc"])).is_err());
+        assert!(decode_values(&SensorType::Snapshot, &values(&["snap"])).is_err());
+    }
+
+    #[test]
+    fn unknown_types_are_text() {
+        assert_eq!(
+            decode_values(
+                &SensorType::Unknown("color".to_string()),
+                &values(&[" red "])
+            ),
+            Ok(DecodedValue::Text(" red ".to_string()))
+        );
+    }
+
+    #[test]
+    fn several_values_decode_to_multi() {
+        assert_eq!(
+            decode_values(&SensorType::Gauge, &values(&["1", "2.5"])),
+            Ok(DecodedValue::Multi(vec![
+                DecodedValue::Number(1.0),
+                DecodedValue::Number(2.5),
+            ]))
+        );
+        let error = decode_values(&SensorType::Gauge, &values(&["1", "x"])).unwrap_err();
+        assert_eq!(error.message, "value[1]: 'x' is not a number");
+    }
+
+    #[test]
+    fn no_values_is_an_error() {
+        assert!(decode_values(&SensorType::Text, &[]).is_err());
+    }
+
+    #[test]
+    fn encoded_values_decode_the_same() {
+        for (kind, value) in [
+            (SensorType::Gauge, DecodedValue::Number(-3.25)),
+            (SensorType::Switch, DecodedValue::Bool(false)),
+            (
+                SensorType::Snapshot,
+                DecodedValue::Snapshot("abc".to_string()),
+            ),
+            (
+                SensorType::Switch,
+                DecodedValue::Multi(vec![DecodedValue::Bool(true), DecodedValue::Bool(false)]),
+            ),
+        ] {
+            assert_eq!(decode_values(&kind, &value.encode()), Ok(value));
+        }
+    }
+}
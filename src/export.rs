@@ -0,0 +1,301 @@
+use core::fmt;
+
+use serde::Serialize;
+
+use crate::decode::{decode_sensor, DecodedValue};
+use crate::models::{RawData, Sensor};
+use crate::parquet::{self, Column, ColumnData};
+use crate::timestamp::Timestamp;
+
+/// File format of an export.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// One JSON object per line.
+    Ndjson,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [
+        ExportFormat::Csv,
+        ExportFormat::Ndjson,
+        ExportFormat::Parquet,
+    ];
+
+    /// Name used in forms, which is also the file extension.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ExportFormat> {
+        ExportFormat::ALL.into_iter().find(|f| f.as_str() == name)
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Csv => write!(f, "CSV"),
+            ExportFormat::Ndjson => write!(f, "JSON Lines"),
+            ExportFormat::Parquet => write!(f, "Parquet"),
+        }
+    }
+}
+
+/// One exported raw data value.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct ExportRow {
+    pub device_id: String,
+    pub sensor_id: String,
+    pub time: Option<Timestamp>,
+    /// The values as reported, before the formula.
+    pub values: Vec<String>,
+    /// The values after the sensor's formula, decoded as in the sensor view.
+    pub value: Option<DecodedValue>,
+    /// Why the values could not be decoded.
+    pub error: Option<String>,
+}
+
+impl ExportRow {
+    pub fn new(sensor: &Sensor, data: &RawData) -> Self {
+        let (value, error) = match decode_sensor(sensor, &data.value) {
+            Ok(value) => (Some(value), None),
+            Err(e) => (None, Some(e)),
+        };
+        ExportRow {
+            device_id: data.device_id.clone(),
+            sensor_id: sensor.id.clone(),
+            time: data.time,
+            values: data.value.clone(),
+            value,
+            error,
+        }
+    }
+}
+
+/// Encodes the rows as a file of the given format.
+///
+/// Every format has the columns `device_id`, `sensor_id`, `time` (UTC), `values`, `value` and
+/// `error`. CSV and Parquet hold `values` as a JSON array, since sensors report any number of
+/// them, and `value` as the sensor view shows it. JSON Lines keeps the type of `value`, and
+/// Parquet stores it as doubles when every value is a single number.
+pub fn encode(format: ExportFormat, rows: &[ExportRow]) -> serde_json::Result<Vec<u8>> {
+    match format {
+        ExportFormat::Csv => encode_csv(rows),
+        ExportFormat::Ndjson => {
+            let mut out = Vec::new();
+            for row in rows {
+                serde_json::to_writer(&mut out, row)?;
+                out.push(b'\n');
+            }
+            Ok(out)
+        }
+        ExportFormat::Parquet => encode_parquet(rows),
+    }
+}
+
+fn encode_csv(rows: &[ExportRow]) -> serde_json::Result<Vec<u8>> {
+    let mut out = String::from("device_id,sensor_id,time,values,value,error\n");
+    for row in rows {
+        let fields = [
+            row.device_id.clone(),
+            row.sensor_id.clone(),
+            row.time.map(|t| t.to_string()).unwrap_or_default(),
+            serde_json::to_string(&row.values)?,
+            row.value
+                .as_ref()
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+            row.error.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    Ok(out.into_bytes())
+}
+
+/// Quotes a CSV field when it holds a separator, a quote or a line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn encode_parquet(rows: &[ExportRow]) -> serde_json::Result<Vec<u8>> {
+    let text = |f: fn(&ExportRow) -> String| {
+        ColumnData::Text(rows.iter().map(|row| Some(f(row))).collect())
+    };
+    let values = rows
+        .iter()
+        .map(|row| serde_json::to_string(&row.values).map(Some))
+        .collect::<serde_json::Result<_>>()?;
+    let columns = [
+        ("device_id", text(|row| row.device_id.clone())),
+        ("sensor_id", text(|row| row.sensor_id.clone())),
+        (
+            "time",
+            ColumnData::TimestampMillis(rows.iter().map(|row| Some(row.time?.millis())).collect()),
+        ),
+        ("values", ColumnData::Text(values)),
+        ("value", value_column(rows)),
+        (
+            "error",
+            ColumnData::Text(rows.iter().map(|row| row.error.clone()).collect()),
+        ),
+    ]
+    .map(|(name, data)| Column {
+        name: name.to_string(),
+        data,
+    });
+    Ok(parquet::write(&columns))
+}
+
+/// The `value` column: doubles when every value is a single number, text otherwise.
+fn value_column(rows: &[ExportRow]) -> ColumnData {
+    let numbers = rows
+        .iter()
+        .map(|row| match &row.value {
+            None => Some(None),
+            Some(DecodedValue::Number(n)) => Some(Some(*n)),
+            Some(_) => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    match numbers {
+        Some(numbers) => ColumnData::Double(numbers),
+        None => ColumnData::Text(
+            rows.iter()
+                .map(|row| row.value.as_ref().map(|v| v.to_string()))
+                .collect(),
+        ),
+    }
+}
+
+/// Hands an export to the user: a download in the browser, a save dialog on the desktop.
+/// Returns `false` when the user cancelled.
+#[cfg(not(feature = "desktop"))]
+pub async fn save_export(
+    file_name: &str,
+    format: ExportFormat,
+    bytes: &[u8],
+) -> Result<bool, String> {
+    use base64::prelude::*;
+    use dioxus::prelude::*;
+
+    let mime = match format {
+        ExportFormat::Csv => "text/csv",
+        ExportFormat::Ndjson => "application/x-ndjson",
+        ExportFormat::Parquet => "application/vnd.apache.parquet",
+    };
+
+    let mut eval = document::eval(
+        r#"
+        const [name, mime, data] = await dioxus.recv();
+        const bytes = Uint8Array.from(atob(data), (c) => c.charCodeAt(0));
+        const url = URL.createObjectURL(new Blob([bytes], { type: mime }));
+        const link = document.createElement("a");
+        link.href = url;
+        link.download = name;
+        document.body.appendChild(link);
+        link.click();
+        link.remove();
+        setTimeout(() => URL.revokeObjectURL(url), 1000);
+        dioxus.send(true);
+        "#,
+    );
+    eval.send((file_name, mime, BASE64_STANDARD.encode(bytes)))
+        .map_err(|e| format!("{e}"))?;
+    eval.recv::<bool>().await.map_err(|e| format!("{e}"))
+}
+
+/// Hands an export to the user: a download in the browser, a save dialog on the desktop.
+/// Returns `false` when the user cancelled.
+#[cfg(feature = "desktop")]
+pub async fn save_export(
+    file_name: &str,
+    format: ExportFormat,
+    bytes: &[u8],
+) -> Result<bool, String> {
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_file_name(file_name)
+        .add_filter(format.to_string(), &[format.as_str()])
+        .save_file()
+        .await
+    else {
+        return Ok(false);
+    };
+    std::fs::write(file.path(), bytes).map_err(|e| format!("{e}"))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SensorType;
+
+    fn row(kind: SensorType, formula: Option<&str>, values: &[&str]) -> ExportRow {
+        let sensor = Sensor {
+            id: "s1".to_string(),
+            kind,
+            formula: formula.map(str::to_string),
+            ..Default::default()
+        };
+        let data = RawData {
+            id: "r1".to_string(),
+            device_id: "d1".to_string(),
+            value: values.iter().map(|v| v.to_string()).collect(),
+            time: None,
+        };
+        ExportRow::new(&sensor, &data)
+    }
+
+    #[test]
+    fn rows_hold_the_decoded_value_or_its_error() {
+        let gauge = row(SensorType::Gauge, Some("value * 2"), &["1.5"]);
+        assert_eq!(gauge.value, Some(DecodedValue::Number(3.0)));
+        assert_eq!(gauge.error, None);
+
+        let broken = row(SensorType::Switch, None, &["maybe"]);
+        assert_eq!(broken.value, None);
+        assert!(broken.error.is_some_and(|e| e.contains("'maybe'")));
+    }
+
+    #[test]
+    fn csv_shows_every_value() {
+        let rows = [row(SensorType::Gauge, None, &["1", "2.5"])];
+        let csv = String::from_utf8(encode(ExportFormat::Csv, &rows).unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "device_id,sensor_id,time,values,value,error\n\
+             d1,s1,,\"[\"\"1\"\",\"\"2.5\"\"]\",\"1, 2.5\",\n"
+        );
+    }
+
+    #[test]
+    fn ndjson_keeps_value_types() {
+        let rows = [
+            row(SensorType::Switch, None, &["on", "0"]),
+            row(SensorType::Snapshot, None, &["snapshot://abc"]),
+        ];
+        let ndjson = String::from_utf8(encode(ExportFormat::Ndjson, &rows).unwrap()).unwrap();
+        let values: Vec<serde_json::Value> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["value"].clone())
+            .collect();
+        assert_eq!(
+            values,
+            [
+                serde_json::json!([true, false]),
+                serde_json::json!("snapshot://abc")
+            ]
+        );
+    }
+}
@@ -0,0 +1,543 @@
+use core::fmt;
+
+/// Error from parsing or evaluating a sensor formula.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormulaError {
+    pub message: String,
+    /// Character offset in the formula, when the error points at a location.
+    pub position: Option<usize>,
+}
+
+impl FormulaError {
+    fn at(position: usize, message: impl Into<String>) -> Self {
+        FormulaError {
+            message: message.into(),
+            position: Some(position),
+        }
+    }
+
+    fn eval(message: impl Into<String>) -> Self {
+        FormulaError {
+            message: message.into(),
+            position: None,
+        }
+    }
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{} at column {}", self.message, position + 1),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for FormulaError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    /// `value[n]`; plain `value` is `value[0]`.
+    Value(usize),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, FormulaError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Exponent, e.g. `1.5e-3`.
+            if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && matches!(chars[j], '+' | '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text
+                .parse::<f64>()
+                .map_err(|_| FormulaError::at(start, format!("Invalid number '{text}'")))?;
+            tokens.push((start, Token::Number(number)));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            tokens.push((start, Token::Ident(name)));
+        } else if "+-*/%^()[],".contains(c) {
+            tokens.push((i, Token::Op(c)));
+            i += 1;
+        } else {
+            return Err(FormulaError::at(i, format!("Unexpected character '{c}'")));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser; `^` binds tighter than unary minus, which binds tighter than `*`.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|(p, _)| *p)
+            .unwrap_or(self.end)
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: char) -> Result<(), FormulaError> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(FormulaError::at(
+                self.position(),
+                format!("Expected '{op}'"),
+            ))
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, FormulaError> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op('+')) => BinaryOp::Add,
+                Some(Token::Op('-')) => BinaryOp::Sub,
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, FormulaError> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op('*')) => BinaryOp::Mul,
+                Some(Token::Op('/')) => BinaryOp::Div,
+                Some(Token::Op('%')) => BinaryOp::Rem,
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, FormulaError> {
+        if self.eat('-') {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Expr, FormulaError> {
+        let base = self.primary()?;
+        if self.eat('^') {
+            // Right associative: 2^3^2 == 2^(3^2).
+            let exponent = self.unary()?;
+            Ok(Expr::Binary(
+                BinaryOp::Pow,
+                Box::new(base),
+                Box::new(exponent),
+            ))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, FormulaError> {
+        let position = self.position();
+        let Some((_, token)) = self.tokens.get(self.pos).cloned() else {
+            return Err(FormulaError::at(position, "Unexpected end of formula"));
+        };
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Op('(') => {
+                let inner = self.expression()?;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            Token::Ident(name) => self.identifier(name, position),
+            Token::Op(op) => Err(FormulaError::at(position, format!("Unexpected '{op}'"))),
+        }
+    }
+
+    fn identifier(&mut self, name: String, position: usize) -> Result<Expr, FormulaError> {
+        match name.as_str() {
+            "value" => {
+                if !self.eat('[') {
+                    return Ok(Expr::Value(0));
+                }
+                let index_position = self.position();
+                let index = match self.tokens.get(self.pos) {
+                    Some((_, Token::Number(n))) if n.fract() == 0.0 && *n >= 0.0 => *n as usize,
+                    _ => {
+                        return Err(FormulaError::at(
+                            index_position,
+                            "Index of value[] must be a whole number",
+                        ))
+                    }
+                };
+                self.pos += 1;
+                self.expect(']')?;
+                Ok(Expr::Value(index))
+            }
+            "pi" => Ok(Expr::Number(std::f64::consts::PI)),
+            "e" => Ok(Expr::Number(std::f64::consts::E)),
+            _ => {
+                let Some((min_args, max_args)) = function_arity(&name) else {
+                    return Err(FormulaError::at(position, format!("Unknown name '{name}'")));
+                };
+                self.expect('(')?;
+                let mut args = Vec::new();
+                if !self.eat(')') {
+                    loop {
+                        args.push(self.expression()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                if args.len() < min_args || args.len() > max_args {
+                    let expected = match (min_args, max_args) {
+                        (min, usize::MAX) => format!("at least {min}"),
+                        (min, max) if min == max => min.to_string(),
+                        (min, max) => format!("{min} or {max}"),
+                    };
+                    return Err(FormulaError::at(
+                        position,
+                        format!("{name}() takes {expected} arguments, got {}", args.len()),
+                    ));
+                }
+                Ok(Expr::Call(name, args))
+            }
+        }
+    }
+}
+
+/// Minimum and maximum argument count of a supported function.
+fn function_arity(name: &str) -> Option<(usize, usize)> {
+    match name {
+        "abs" | "sqrt" | "cbrt" | "exp" | "ln" | "log10" | "log2" | "sin" | "cos" | "tan"
+        | "asin" | "acos" | "atan" | "floor" | "ceil" | "trunc" | "sign" => Some((1, 1)),
+        "round" | "log" => Some((1, 2)),
+        "pow" | "atan2" => Some((2, 2)),
+        "clamp" => Some((3, 3)),
+        "min" | "max" => Some((1, usize::MAX)),
+        _ => None,
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> f64 {
+    match (name, args) {
+        ("abs", [x]) => x.abs(),
+        ("sqrt", [x]) => x.sqrt(),
+        ("cbrt", [x]) => x.cbrt(),
+        ("exp", [x]) => x.exp(),
+        ("ln", [x]) | ("log", [x]) => x.ln(),
+        ("log", [x, base]) => x.log(*base),
+        ("log10", [x]) => x.log10(),
+        ("log2", [x]) => x.log2(),
+        ("sin", [x]) => x.sin(),
+        ("cos", [x]) => x.cos(),
+        ("tan", [x]) => x.tan(),
+        ("asin", [x]) => x.asin(),
+        ("acos", [x]) => x.acos(),
+        ("atan", [x]) => x.atan(),
+        ("atan2", [y, x]) => y.atan2(*x),
+        ("floor", [x]) => x.floor(),
+        ("ceil", [x]) => x.ceil(),
+        ("trunc", [x]) => x.trunc(),
+        ("sign", [x]) => {
+            if *x == 0.0 {
+                0.0
+            } else {
+                x.signum()
+            }
+        }
+        ("round", [x]) => x.round(),
+        ("round", [x, digits]) => {
+            let scale = 10f64.powi(*digits as i32);
+            (x * scale).round() / scale
+        }
+        ("pow", [x, y]) => x.powf(*y),
+        ("clamp", [x, lo, hi]) => x.max(*lo).min(*hi),
+        ("min", args) => args.iter().copied().fold(f64::INFINITY, f64::min),
+        ("max", args) => args.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        _ => f64::NAN,
+    }
+}
+
+/// A parsed sensor formula such as `value[0] * 0.1 + 20` or `round(sqrt(value[1]), 2)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    expr: Expr,
+}
+
+impl Formula {
+    pub fn parse(source: &str) -> Result<Self, FormulaError> {
+        let tokens = tokenize(source)?;
+        if tokens.is_empty() {
+            return Err(FormulaError::at(0, "Formula is empty"));
+        }
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: source.chars().count(),
+        };
+        let expr = parser.expression()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(FormulaError::at(parser.position(), "Unexpected input"));
+        }
+        Ok(Formula { expr })
+    }
+
+    /// Evaluates the formula against a sensor's raw values.
+    pub fn eval(&self, values: &[String]) -> Result<f64, FormulaError> {
+        let result = Self::eval_expr(&self.expr, values)?;
+        if result.is_finite() {
+            Ok(result)
+        } else {
+            Err(FormulaError::eval(format!(
+                "Result is not a finite number ({result})"
+            )))
+        }
+    }
+
+    fn eval_expr(expr: &Expr, values: &[String]) -> Result<f64, FormulaError> {
+        Ok(match expr {
+            Expr::Number(n) => *n,
+            Expr::Value(index) => {
+                let raw = values.get(*index).ok_or_else(|| {
+                    FormulaError::eval(format!(
+                        "value[{index}] does not exist, the sensor has {} value(s)",
+                        values.len()
+                    ))
+                })?;
+                raw.trim().parse::<f64>().map_err(|_| {
+                    FormulaError::eval(format!("value[{index}] '{raw}' is not a number"))
+                })?
+            }
+            Expr::Neg(inner) => -Self::eval_expr(inner, values)?,
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = Self::eval_expr(lhs, values)?;
+                let rhs = Self::eval_expr(rhs, values)?;
+                match op {
+                    BinaryOp::Add => lhs + rhs,
+                    BinaryOp::Sub => lhs - rhs,
+                    BinaryOp::Mul => lhs * rhs,
+                    BinaryOp::Div if rhs == 0.0 => {
+                        return Err(FormulaError::eval("Division by zero"))
+                    }
+                    BinaryOp::Div => lhs / rhs,
+                    BinaryOp::Rem if rhs == 0.0 => {
+                        return Err(FormulaError::eval("Division by zero"))
+                    }
+                    BinaryOp::Rem => lhs % rhs,
+                    BinaryOp::Pow => lhs.powf(rhs),
+                }
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|a| Self::eval_expr(a, values))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call(name, &args)
+            }
+        })
+    }
+}
+
+/// Applies an optional formula to raw values; without a formula the values are returned as is.
+pub fn apply_formula(
+    formula: Option<&str>,
+    values: &[String],
+) -> Result<Vec<String>, FormulaError> {
+    match formula.map(str::trim).filter(|f| !f.is_empty()) {
+        Some(formula) => {
+            let result = Formula::parse(formula)?.eval(values)?;
+            Ok(vec![format_number(result)])
+        }
+        None => Ok(values.to_vec()),
+    }
+}
+
+/// Prints a formula result without float noise such as `0.30000000000000004`.
+fn format_number(value: f64) -> String {
+    let rounded = format!("{value:.6}");
+    let trimmed = rounded.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str, values: &[&str]) -> Result<f64, FormulaError> {
+        let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        Formula::parse(source)?.eval(&values)
+    }
+
+    fn error(source: &str, values: &[&str]) -> String {
+        eval(source, values).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3", &[]), Ok(7.0));
+        assert_eq!(eval("(1 + 2) * 3", &[]), Ok(9.0));
+        assert_eq!(eval("10 - 4 - 3", &[]), Ok(3.0));
+        assert_eq!(eval("2 ^ 3 ^ 2", &[]), Ok(512.0));
+        assert_eq!(eval("7 % 4 * 2", &[]), Ok(6.0));
+        assert_eq!(eval("1.5e1 / 3", &[]), Ok(5.0));
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(eval("-2 ^ 2", &[]), Ok(-4.0));
+        assert_eq!(eval("2 ^ -1", &[]), Ok(0.5));
+        assert_eq!(eval("3 * -value", &["2"]), Ok(-6.0));
+        assert_eq!(eval("--1 + +1", &[]), Ok(2.0));
+    }
+
+    #[test]
+    fn values_and_functions() {
+        assert_eq!(eval("value * 0.5 + 20", &["4"]), Ok(22.0));
+        assert_eq!(eval("value[1] - value[0]", &["1", " 3 "]), Ok(2.0));
+        assert_eq!(eval("round(sqrt(value[0]), 2)", &["2"]), Ok(1.41));
+        assert_eq!(eval("max(1, value, 3)", &["5"]), Ok(5.0));
+        assert_eq!(eval("clamp(value, 0, 100)", &["120"]), Ok(100.0));
+    }
+
+    #[test]
+    fn value_out_of_range() {
+        assert_eq!(
+            error("value[2]", &["1", "2"]),
+            "value[2] does not exist, the sensor has 2 value(s)"
+        );
+        assert_eq!(
+            error("value", &[]),
+            "value[0] does not exist, the sensor has 0 value(s)"
+        );
+        assert_eq!(error("value[0]", &["on"]), "value[0] 'on' is not a number");
+        assert_eq!(
+            error("value[1.5]", &[]),
+            "Index of value[] must be a whole number at column 7"
+        );
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(error("value / 0", &["1"]), "Division by zero");
+        assert_eq!(error("1 % (value - 1)", &["1"]), "Division by zero");
+        assert_eq!(error("ln(0)", &[]), "Result is not a finite number (-inf)");
+    }
+
+    #[test]
+    fn unknown_function() {
+        assert_eq!(error("foo(1)", &[]), "Unknown name 'foo' at column 1");
+        assert_eq!(error("2 * x0", &[]), "Unknown name 'x0' at column 5");
+        assert_eq!(
+            error("pow(2)", &[]),
+            "pow() takes 2 arguments, got 1 at column 1"
+        );
+        assert_eq!(
+            error("min()", &[]),
+            "min() takes at least 1 arguments, got 0 at column 1"
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(error("", &[]), "Formula is empty at column 1");
+        assert_eq!(error("1 +", &[]), "Unexpected end of formula at column 4");
+        assert_eq!(error("(1 + 2", &[]), "Expected ')' at column 7");
+        assert_eq!(error("1 2", &[]), "Unexpected input at column 3");
+        assert_eq!(error("1 $ 2", &[]), "Unexpected character '$' at column 3");
+        assert_eq!(error("*2", &[]), "Unexpected '*' at column 1");
+    }
+
+    #[test]
+    fn apply_formula_formats_the_result() {
+        let values = vec!["0.1".to_string(), "0.2".to_string()];
+        assert_eq!(
+            apply_formula(Some("value[0] + value[1]"), &values),
+            Ok(vec!["0.3".to_string()])
+        );
+        assert_eq!(
+            apply_formula(Some("-0 * 1"), &values),
+            Ok(vec!["0".to_string()])
+        );
+        assert_eq!(apply_formula(Some("  "), &values), Ok(values.clone()));
+        assert_eq!(apply_formula(None, &values), Ok(values.clone()));
+    }
+
+    #[test]
+    fn error_position() {
+        let err = Formula::parse("value + foo").unwrap_err();
+        assert_eq!(err.position, Some(8));
+        assert_eq!(err.message, "Unknown name 'foo'");
+    }
+}
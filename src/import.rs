@@ -0,0 +1,826 @@
+use core::fmt;
+
+use serde::Deserialize;
+
+use crate::api::{ApiClient, ApiResult};
+use crate::formula::Formula;
+use crate::models::{Attribute, Device, EditDevice, EditSensor, Sensor, SensorType};
+
+/// A device and its sensors as described by an import file.
+///
+/// Fields left out keep their current value when the device exists; `name` and `type` are
+/// required for new devices.
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct DevicePatch {
+    pub id: String,
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub desc: Option<String>,
+    pub uri: Option<String>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    /// Merged by key into the current attributes.
+    pub attributes: Option<Vec<Attribute>>,
+    #[serde(default)]
+    pub sensors: Vec<SensorPatch>,
+}
+
+/// A sensor as described by an import file, with the same rules as [`DevicePatch`].
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SensorPatch {
+    pub id: String,
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub desc: Option<String>,
+    pub uri: Option<String>,
+    pub formula: Option<String>,
+    pub attributes: Option<Vec<Attribute>>,
+}
+
+/// Columns of a CSV import. Each row describes a sensor, or only a device when `sensor_id` is
+/// empty; device columns are read from the first row of each device.
+const CSV_COLUMNS: [&str; 15] = [
+    "device_id",
+    "device_name",
+    "device_type",
+    "device_desc",
+    "device_uri",
+    "lat",
+    "lon",
+    "device_attributes",
+    "sensor_id",
+    "sensor_name",
+    "sensor_type",
+    "sensor_desc",
+    "sensor_uri",
+    "formula",
+    "sensor_attributes",
+];
+
+/// Reads an import file: a JSON array of devices with their `sensors`, or a CSV file with the
+/// columns in [`CSV_COLUMNS`].
+pub fn parse_import(file_name: &str, text: &str) -> Result<Vec<DevicePatch>, Vec<String>> {
+    let is_json = file_name.to_ascii_lowercase().ends_with(".json")
+        || text.trim_start().starts_with('[');
+    if is_json {
+        serde_json::from_str(text).map_err(|e| vec![format!("Invalid JSON: {e}")])
+    } else {
+        parse_csv_import(text)
+    }
+}
+
+fn parse_csv_import(text: &str) -> Result<Vec<DevicePatch>, Vec<String>> {
+    let mut records = parse_csv(text).map_err(|e| vec![e])?.into_iter();
+    let Some(header) = records.next() else {
+        return Err(vec!["The file is empty".to_string()]);
+    };
+    let header: Vec<String> = header.iter().map(|h| h.trim().to_ascii_lowercase()).collect();
+    let mut errors: Vec<String> = header
+        .iter()
+        .filter(|h| !CSV_COLUMNS.contains(&h.as_str()))
+        .map(|h| format!("Unknown column '{h}'"))
+        .collect();
+    if !header.iter().any(|h| h == "device_id") {
+        errors.push("Missing column 'device_id'".to_string());
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut devices: Vec<DevicePatch> = Vec::new();
+    for (i, record) in records.enumerate() {
+        // Row 1 is the header.
+        let row = i + 2;
+        if record.iter().all(|v| v.trim().is_empty()) {
+            continue;
+        }
+        if record.len() != header.len() {
+            errors.push(format!(
+                "Row {row}: expected {} columns, found {}",
+                header.len(),
+                record.len()
+            ));
+            continue;
+        }
+        let cell = |name: &str| {
+            header
+                .iter()
+                .position(|h| h == name)
+                .and_then(|i| record.get(i))
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let number = |name: &str| match cell(name) {
+            Some(v) => v
+                .parse::<f64>()
+                .map(Some)
+                .map_err(|_| format!("Row {row}: {name} must be a number")),
+            None => Ok(None),
+        };
+        let attributes = |name: &str| cell(name).map(|v| parse_attributes(&v)).transpose();
+
+        let Some(device_id) = cell("device_id") else {
+            errors.push(format!("Row {row}: device_id is required"));
+            continue;
+        };
+        let index = match devices.iter().position(|d| d.id == device_id) {
+            Some(index) => index,
+            None => {
+                let (lat, lon) = (number("lat"), number("lon"));
+                let device_attributes = attributes("device_attributes");
+                let device = DevicePatch {
+                    id: device_id,
+                    name: cell("device_name"),
+                    kind: cell("device_type"),
+                    desc: cell("device_desc"),
+                    uri: cell("device_uri"),
+                    lat: lat.unwrap_or_else(|e| {
+                        errors.push(e);
+                        None
+                    }),
+                    lon: lon.unwrap_or_else(|e| {
+                        errors.push(e);
+                        None
+                    }),
+                    attributes: device_attributes.unwrap_or_else(|e| {
+                        errors.push(format!("Row {row}: {e}"));
+                        None
+                    }),
+                    sensors: Vec::new(),
+                };
+                devices.push(device);
+                devices.len() - 1
+            }
+        };
+
+        if let Some(sensor_id) = cell("sensor_id") {
+            let sensor_attributes = attributes("sensor_attributes");
+            devices[index].sensors.push(SensorPatch {
+                id: sensor_id,
+                name: cell("sensor_name"),
+                kind: cell("sensor_type"),
+                desc: cell("sensor_desc"),
+                uri: cell("sensor_uri"),
+                formula: cell("formula"),
+                attributes: sensor_attributes.unwrap_or_else(|e| {
+                    errors.push(format!("Row {row}: {e}"));
+                    None
+                }),
+            });
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(devices)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parses `key=value;key=value` attributes of a CSV cell.
+fn parse_attributes(text: &str) -> Result<Vec<Attribute>, String> {
+    text.split(';')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => Ok(Attribute {
+                key: key.trim().to_string(),
+                value: value.trim().to_string(),
+            }),
+            _ => Err(format!("attribute '{pair}' is not key=value")),
+        })
+        .collect()
+}
+
+/// Splits CSV text into records, following RFC 4180 quoting.
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("Unterminated quoted field".to_string());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// What an import step does to the endpoint.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ImportAction {
+    Create,
+    Update,
+    /// The file matches the current device or sensor.
+    Skip,
+}
+
+impl fmt::Display for ImportAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportAction::Create => write!(f, "Create"),
+            ImportAction::Update => write!(f, "Update"),
+            ImportAction::Skip => write!(f, "Skip"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ImportRequest {
+    Device(EditDevice),
+    Sensor { id: String, sensor: EditSensor },
+}
+
+/// One device or sensor of the dry run, with the request that applies it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ImportStep {
+    pub device_id: String,
+    pub action: ImportAction,
+    /// Names of the fields an update changes.
+    pub changes: Vec<String>,
+    pub request: ImportRequest,
+}
+
+impl ImportStep {
+    /// `device` or `device/sensor`.
+    pub fn target(&self) -> String {
+        match &self.request {
+            ImportRequest::Device(_) => self.device_id.clone(),
+            ImportRequest::Sensor { id, .. } => format!("{}/{id}", self.device_id),
+        }
+    }
+
+    pub async fn apply(&self, api: &ApiClient) -> ApiResult<()> {
+        let device_id = &self.device_id;
+        match (&self.request, self.action) {
+            (_, ImportAction::Skip) => {}
+            (ImportRequest::Device(device), ImportAction::Create) => {
+                api.create_device(device).await?;
+            }
+            (ImportRequest::Device(device), ImportAction::Update) => {
+                api.update_device(device_id, device).await?;
+            }
+            (ImportRequest::Sensor { sensor, .. }, ImportAction::Create) => {
+                api.create_sensor(device_id, sensor).await?;
+            }
+            (ImportRequest::Sensor { id, sensor }, ImportAction::Update) => {
+                api.update_sensor(device_id, id, sensor).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares the file with the current devices and lists what applying it would do, devices
+/// before their sensors. Fails with every problem found when the file cannot be applied.
+pub fn plan_import(
+    devices: &[Device],
+    patches: &[DevicePatch],
+) -> Result<Vec<ImportStep>, Vec<String>> {
+    let mut steps = Vec::new();
+    let mut errors = Vec::new();
+    for (i, patch) in patches.iter().enumerate() {
+        let device_id = patch.id.trim();
+        if let Err(e) = check_id(device_id) {
+            errors.push(format!("Device '{device_id}': {e}"));
+            continue;
+        }
+        if patches[..i].iter().any(|p| p.id.trim() == device_id) {
+            errors.push(format!("Device '{device_id}' is listed twice"));
+            continue;
+        }
+        let current = devices.iter().find(|d| d.id == device_id);
+        match plan_device(current, patch) {
+            Ok(step) => steps.push(step),
+            Err(e) => errors.extend(e.into_iter().map(|e| format!("Device '{device_id}': {e}"))),
+        }
+
+        let sensors = current.and_then(|d| d.sensors.as_deref()).unwrap_or_default();
+        for (j, sensor) in patch.sensors.iter().enumerate() {
+            let sensor_id = sensor.id.trim();
+            let target = format!("Sensor '{device_id}/{sensor_id}'");
+            if let Err(e) = check_id(sensor_id) {
+                errors.push(format!("{target}: {e}"));
+                continue;
+            }
+            if patch.sensors[..j].iter().any(|s| s.id.trim() == sensor_id) {
+                errors.push(format!("{target} is listed twice"));
+                continue;
+            }
+            let current = sensors.iter().find(|s| s.id == sensor_id);
+            match plan_sensor(device_id, current, sensor) {
+                Ok(step) => steps.push(step),
+                Err(e) => errors.extend(e.into_iter().map(|e| format!("{target}: {e}"))),
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(steps)
+    } else {
+        Err(errors)
+    }
+}
+
+fn check_id(id: &str) -> Result<(), String> {
+    if id.is_empty() {
+        Err("ID is required".to_string())
+    } else if id.contains(|c: char| c.is_whitespace() || c == '/') {
+        Err("ID must not contain spaces or '/'".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Trims a text field, reading an empty one as left out.
+fn given(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// Sets the given attributes, keeping the other current ones.
+fn merge_attributes(
+    current: &Option<Vec<Attribute>>,
+    given: &Option<Vec<Attribute>>,
+) -> Option<Vec<Attribute>> {
+    let Some(given) = given else {
+        return current.clone();
+    };
+    let mut merged = current.clone().unwrap_or_default();
+    for attribute in given {
+        match merged.iter_mut().find(|a| a.key == attribute.key) {
+            Some(existing) => existing.value = attribute.value.clone(),
+            None => merged.push(attribute.clone()),
+        }
+    }
+    Some(merged).filter(|a| !a.is_empty())
+}
+
+fn check_coordinate(value: Option<f64>, name: &str, limit: f64) -> Result<(), String> {
+    match value {
+        Some(v) if !v.is_finite() || v.abs() > limit => {
+            Err(format!("{name} must be between -{limit} and {limit}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn plan_device(current: Option<&Device>, patch: &DevicePatch) -> Result<ImportStep, Vec<String>> {
+    let mut errors = Vec::new();
+    if let Err(e) = check_coordinate(patch.lat, "Latitude", 90.0) {
+        errors.push(e);
+    }
+    if let Err(e) = check_coordinate(patch.lon, "Longitude", 180.0) {
+        errors.push(e);
+    }
+    let base = current.map(EditDevice::from);
+    let name = given(&patch.name).or_else(|| base.as_ref().map(|b| b.name.clone()));
+    let kind = given(&patch.kind).or_else(|| base.as_ref().map(|b| b.kind.clone()));
+    if name.is_none() {
+        errors.push("Name is required for a new device".to_string());
+    }
+    if kind.is_none() {
+        errors.push("Type is required for a new device".to_string());
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let base = base.unwrap_or_default();
+    let device = EditDevice {
+        id: None,
+        name: name.unwrap_or_default(),
+        kind: kind.unwrap_or_default(),
+        desc: given(&patch.desc).or(base.desc.clone()),
+        uri: given(&patch.uri).or(base.uri.clone()),
+        lat: patch.lat.or(base.lat),
+        lon: patch.lon.or(base.lon),
+        attributes: merge_attributes(&base.attributes, &patch.attributes),
+    };
+    let (action, changes, device) = if current.is_some() {
+        let changes: Vec<String> = [
+            ("name", device.name != base.name),
+            ("type", device.kind != base.kind),
+            ("desc", device.desc != base.desc),
+            ("uri", device.uri != base.uri),
+            ("lat", device.lat != base.lat),
+            ("lon", device.lon != base.lon),
+            ("attributes", device.attributes != base.attributes),
+        ]
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(field, _)| field.to_string())
+        .collect();
+        let action = if changes.is_empty() {
+            ImportAction::Skip
+        } else {
+            ImportAction::Update
+        };
+        (action, changes, device)
+    } else {
+        let device = EditDevice {
+            id: Some(patch.id.trim().to_string()),
+            ..device
+        };
+        (ImportAction::Create, Vec::new(), device)
+    };
+    Ok(ImportStep {
+        device_id: patch.id.trim().to_string(),
+        action,
+        changes,
+        request: ImportRequest::Device(device),
+    })
+}
+
+fn plan_sensor(
+    device_id: &str,
+    current: Option<&Sensor>,
+    patch: &SensorPatch,
+) -> Result<ImportStep, Vec<String>> {
+    let mut errors = Vec::new();
+    let base = current.map(EditSensor::from);
+    let name = given(&patch.name).or_else(|| base.as_ref().map(|b| b.name.clone()));
+    if name.is_none() {
+        errors.push("Name is required for a new sensor".to_string());
+    }
+    let kind = match given(&patch.kind) {
+        Some(kind) => match SensorType::from_api_name(&kind.to_ascii_lowercase()) {
+            Some(kind) => Some(kind),
+            None => {
+                errors.push(format!("Unknown type '{kind}'"));
+                None
+            }
+        },
+        None => base.as_ref().map(|b| b.kind.clone()),
+    };
+    if kind.is_none() && given(&patch.kind).is_none() {
+        errors.push("Type is required for a new sensor".to_string());
+    }
+    let formula = given(&patch.formula);
+    if let Some(formula) = &formula {
+        if let Some(kind) = kind.as_ref().filter(|k| !k.has_formula()) {
+            errors.push(format!("{kind} sensors have no formula"));
+        } else if let Err(e) = Formula::parse(formula) {
+            errors.push(format!("Formula error: {e}"));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let base = base.unwrap_or_default();
+    let sensor = EditSensor {
+        id: None,
+        name: name.unwrap_or_default(),
+        kind: kind.unwrap_or_default(),
+        desc: given(&patch.desc).or(base.desc.clone()),
+        uri: given(&patch.uri).or(base.uri.clone()),
+        formula: formula.or(base.formula.clone()),
+        attributes: merge_attributes(&base.attributes, &patch.attributes),
+    };
+    let (action, changes, sensor) = if current.is_some() {
+        let changes: Vec<String> = [
+            ("name", sensor.name != base.name),
+            ("type", sensor.kind != base.kind),
+            ("desc", sensor.desc != base.desc),
+            ("uri", sensor.uri != base.uri),
+            ("formula", sensor.formula != base.formula),
+            ("attributes", sensor.attributes != base.attributes),
+        ]
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(field, _)| field.to_string())
+        .collect();
+        let action = if changes.is_empty() {
+            ImportAction::Skip
+        } else {
+            ImportAction::Update
+        };
+        (action, changes, sensor)
+    } else {
+        let sensor = EditSensor {
+            id: Some(patch.id.trim().to_string()),
+            ..sensor
+        };
+        (ImportAction::Create, Vec::new(), sensor)
+    };
+    Ok(ImportStep {
+        device_id: device_id.to_string(),
+        action,
+        changes,
+        request: ImportRequest::Sensor {
+            id: patch.id.trim().to_string(),
+            sensor,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "device_id,device_name,device_type,device_desc,device_uri,lat,lon,device_attributes,sensor_id,sensor_name,sensor_type,sensor_desc,sensor_uri,formula,sensor_attributes\n";
+
+    fn device() -> Device {
+        Device {
+            id: "d1".to_string(),
+            name: "Boiler".to_string(),
+            desc: Some("Basement".to_string()),
+            kind: "heater".to_string(),
+            uri: None,
+            lat: Some(52.5),
+            lon: Some(13.4),
+            attributes: Some(vec![Attribute {
+                key: "room".to_string(),
+                value: "cellar".to_string(),
+            }]),
+            sensors: Some(vec![Sensor {
+                id: "t".to_string(),
+                name: "Temperature".to_string(),
+                desc: None,
+                kind: SensorType::Gauge,
+                uri: None,
+                formula: Some("value * 0.1".to_string()),
+                attributes: None,
+            }]),
+        }
+    }
+
+    #[test]
+    fn csv_quoted_fields() {
+        let text = "a,b,c\r\n\"x, y\",\"line 1\nline 2\",\"say \"\"hi\"\"\"\n1,,3";
+        assert_eq!(
+            parse_csv(text),
+            Ok(vec![
+                vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                vec![
+                    "x, y".to_string(),
+                    "line 1\nline 2".to_string(),
+                    "say \"hi\"".to_string()
+                ],
+                vec!["1".to_string(), String::new(), "3".to_string()],
+            ])
+        );
+        assert_eq!(
+            parse_csv("a,\"b\n"),
+            Err("Unterminated quoted field".to_string())
+        );
+    }
+
+    #[test]
+    fn csv_import_with_quoted_fields() {
+        let text = format!(
+            "{HEADER}d1,Boiler,heater,\"Basement, left\",,52.5,13.4,room=cellar;floor=-1,t,Temperature,gauge,\"Water\ntemperature\",,value * 0.1,\n\
+             d1,,,,,,,,h,Humidity,gauge,,,,\n"
+        );
+        let devices = parse_import("devices.csv", &text).unwrap();
+        assert_eq!(devices.len(), 1);
+        let device = &devices[0];
+        assert_eq!(device.desc.as_deref(), Some("Basement, left"));
+        assert_eq!(device.lat, Some(52.5));
+        assert_eq!(
+            device.attributes,
+            Some(vec![
+                Attribute {
+                    key: "room".to_string(),
+                    value: "cellar".to_string()
+                },
+                Attribute {
+                    key: "floor".to_string(),
+                    value: "-1".to_string()
+                },
+            ])
+        );
+        assert_eq!(device.sensors.len(), 2);
+        assert_eq!(
+            device.sensors[0].desc.as_deref(),
+            Some("Water\ntemperature")
+        );
+        assert_eq!(device.sensors[0].formula.as_deref(), Some("value * 0.1"));
+        assert_eq!(device.sensors[1].name.as_deref(), Some("Humidity"));
+    }
+
+    #[test]
+    fn csv_import_wrong_column_count() {
+        let text = format!("{HEADER}d1,Boiler,heater\n\nd2,,,,,,,,,,,,,,,\n");
+        assert_eq!(
+            parse_import("devices.csv", &text),
+            Err(vec![
+                "Row 2: expected 15 columns, found 3".to_string(),
+                "Row 4: expected 15 columns, found 16".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn csv_import_errors() {
+        assert_eq!(
+            parse_import("devices.csv", "device_id,colour\n"),
+            Err(vec!["Unknown column 'colour'".to_string()])
+        );
+        assert_eq!(
+            parse_import("devices.csv", "name\n"),
+            Err(vec![
+                "Unknown column 'name'".to_string(),
+                "Missing column 'device_id'".to_string()
+            ])
+        );
+        assert_eq!(
+            parse_import(
+                "devices.csv",
+                "device_id,lat,device_attributes\n,1,\nd1,north,room\n"
+            ),
+            Err(vec![
+                "Row 2: device_id is required".to_string(),
+                "Row 3: lat must be a number".to_string(),
+                "Row 3: attribute 'room' is not key=value".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn json_import() {
+        let text = r#"[{"id": "d1", "name": "Boiler", "sensors": [{"id": "t", "type": "gauge"}]}]"#;
+        let devices = parse_import("devices.json", text).unwrap();
+        assert_eq!(devices[0].name.as_deref(), Some("Boiler"));
+        assert_eq!(devices[0].sensors[0].kind.as_deref(), Some("gauge"));
+
+        let errors =
+            parse_import("devices.json", r#"[{"id": "d1", "colour": "red"}]"#).unwrap_err();
+        assert!(errors[0].starts_with("Invalid JSON: unknown field `colour`"));
+    }
+
+    #[test]
+    fn unchanged_device_is_skipped() {
+        let patch = DevicePatch {
+            id: "d1".to_string(),
+            name: Some("Boiler".to_string()),
+            desc: Some(" ".to_string()),
+            lat: Some(52.5),
+            attributes: Some(vec![Attribute {
+                key: "room".to_string(),
+                value: "cellar".to_string(),
+            }]),
+            sensors: vec![SensorPatch {
+                id: "t".to_string(),
+                formula: Some("value * 0.1".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let steps = plan_import(&[device()], &[patch]).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert!(steps.iter().all(|s| s.action == ImportAction::Skip));
+        assert!(steps.iter().all(|s| s.changes.is_empty()));
+        assert_eq!(steps[1].target(), "d1/t");
+    }
+
+    #[test]
+    fn changed_and_new_entries() {
+        let patches = [
+            DevicePatch {
+                id: "d1".to_string(),
+                name: Some("Main boiler".to_string()),
+                attributes: Some(vec![Attribute {
+                    key: "floor".to_string(),
+                    value: "-1".to_string(),
+                }]),
+                sensors: vec![SensorPatch {
+                    id: "p".to_string(),
+                    name: Some("Pressure".to_string()),
+                    kind: Some("Gauge".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            DevicePatch {
+                id: "d2".to_string(),
+                name: Some("Pump".to_string()),
+                kind: Some("pump".to_string()),
+                ..Default::default()
+            },
+        ];
+        let steps = plan_import(&[device()], &patches).unwrap();
+        let actions: Vec<_> = steps.iter().map(|s| (s.target(), s.action)).collect();
+        assert_eq!(
+            actions,
+            [
+                ("d1".to_string(), ImportAction::Update),
+                ("d1/p".to_string(), ImportAction::Create),
+                ("d2".to_string(), ImportAction::Create),
+            ]
+        );
+        assert_eq!(steps[0].changes, ["name", "attributes"]);
+        let ImportRequest::Device(edit) = &steps[0].request else {
+            panic!("not a device request");
+        };
+        // Attributes are merged, and fields left out keep their value.
+        assert_eq!(edit.attributes.as_ref().map(Vec::len), Some(2));
+        assert_eq!(edit.desc.as_deref(), Some("Basement"));
+        let ImportRequest::Device(edit) = &steps[2].request else {
+            panic!("not a device request");
+        };
+        assert_eq!(edit.id.as_deref(), Some("d2"));
+    }
+
+    #[test]
+    fn unknown_sensor_type() {
+        let patch = DevicePatch {
+            id: "d1".to_string(),
+            sensors: vec![SensorPatch {
+                id: "x".to_string(),
+                name: Some("Mystery".to_string()),
+                kind: Some("thermometer".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            plan_import(&[device()], &[patch]),
+            Err(vec!["Sensor 'd1/x': Unknown type 'thermometer'".to_string()])
+        );
+    }
+
+    #[test]
+    fn formula_only_on_gauges() {
+        let patch = DevicePatch {
+            id: "d1".to_string(),
+            sensors: vec![SensorPatch {
+                id: "s".to_string(),
+                name: Some("Door".to_string()),
+                kind: Some("switch".to_string()),
+                formula: Some("value * 2".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            plan_import(&[device()], &[patch]),
+            Err(vec!["Sensor 'd1/s': Switch sensors have no formula".to_string()])
+        );
+    }
+
+    #[test]
+    fn plan_errors() {
+        let patches = [
+            DevicePatch {
+                id: "new device".to_string(),
+                ..Default::default()
+            },
+            DevicePatch {
+                id: "d2".to_string(),
+                lat: Some(91.0),
+                sensors: vec![SensorPatch {
+                    id: "t".to_string(),
+                    formula: Some("value +".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            DevicePatch {
+                id: "d2".to_string(),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            plan_import(&[], &patches),
+            Err(vec![
+                "Device 'new device': ID must not contain spaces or '/'".to_string(),
+                "Device 'd2': Latitude must be between -90 and 90".to_string(),
+                "Device 'd2': Name is required for a new device".to_string(),
+                "Device 'd2': Type is required for a new device".to_string(),
+                "Sensor 'd2/t': Name is required for a new sensor".to_string(),
+                "Sensor 'd2/t': Type is required for a new sensor".to_string(),
+                "Sensor 'd2/t': Formula error: Unexpected end of formula at column 8".to_string(),
+                "Device 'd2' is listed twice".to_string(),
+            ])
+        );
+    }
+}
@@ -3,7 +3,8 @@
 use dioxus::prelude::*;
 
 use views::{
-    Blog, DevicePage3, EndpointView, Home, Navbar, ProjectsView, SensorPanel, Storage, Storage2,
+    Blog, DevicePage3, EndpointView, Home, Navbar, ProjectsView, SensorPanel, SettingsView, Storage,
+    Storage2,
 };
 
 use crate::views::Providers;
@@ -16,6 +17,26 @@ mod views;
 mod models;
 /// Centralized persistence helpers.
 mod persistence;
+/// Typed HTTP client for the endpoint API.
+mod api;
+/// Sensor formula parsing and evaluation.
+mod formula;
+/// Typed decoding of raw sensor values.
+mod decode;
+/// Display units, precision and unit conversion.
+mod units;
+/// Threshold rules of sensor values.
+mod alerts;
+/// Parsing and display of API timestamps.
+mod timestamp;
+/// In-memory cache of downloaded snapshots.
+mod snapshot_cache;
+/// Minimal Parquet file writer.
+mod parquet;
+/// Export of sensor data to CSV, JSON Lines and Parquet files.
+mod export;
+/// Bulk import of devices and sensors from CSV and JSON files.
+mod import;
 
 /// The Route enum is used to define the structure of internal routes in our app. All route enums need to derive
 /// the [`Routable`] trait, which provides the necessary methods for the router to work.
@@ -56,6 +77,9 @@ enum Route {
 
         #[route("/projects/:project_name")]
         DevicePage3 {project_name: String},
+
+        #[route("/settings")]
+        SettingsView {},
 }
 
 // We can import assets in dioxus with the `asset!` macro. This macro takes a path to an asset relative to the crate root.
@@ -1,16 +1,75 @@
 use dioxus::prelude::*;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use core::fmt;
 use std::collections::HashMap;
 
-#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
-#[serde(rename_all = "lowercase")]
+use crate::timestamp::{lenient, Timestamp, TimeZoneChoice, DEFAULT_TIME_FORMAT};
+use crate::units::UnitSystem;
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub enum SensorType {
     #[default]
     Gauge,
     Text,
     Switch,
     Snapshot,
+    /// A type this viewer does not know, kept so it is sent back unchanged.
+    Unknown(String),
+}
+
+impl SensorType {
+    pub const ALL: [SensorType; 4] = [
+        SensorType::Gauge,
+        SensorType::Text,
+        SensorType::Switch,
+        SensorType::Snapshot,
+    ];
+
+    /// The name used by the API for this type.
+    pub fn as_str(&self) -> &str {
+        match self {
+            SensorType::Gauge => "gauge",
+            SensorType::Text => "text",
+            SensorType::Switch => "switch",
+            SensorType::Snapshot => "snapshot",
+            SensorType::Unknown(name) => name,
+        }
+    }
+
+    /// The known type with this API name.
+    pub fn from_api_name(name: &str) -> Option<SensorType> {
+        SensorType::ALL.into_iter().find(|t| t.as_str() == name)
+    }
+
+    /// Whether the sensor's formula applies; other types report values that aren't numbers.
+    pub fn has_formula(&self) -> bool {
+        *self == SensorType::Gauge
+    }
+}
+
+impl Serialize for SensorType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for SensorType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(SensorType::from_api_name(&name).unwrap_or(SensorType::Unknown(name)))
+    }
+}
+
+impl fmt::Display for SensorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SensorType::Gauge => write!(f, "Gauge"),
+            SensorType::Text => write!(f, "Text"),
+            SensorType::Switch => write!(f, "Switch"),
+            SensorType::Snapshot => write!(f, "Snapshot"),
+            SensorType::Unknown(name) => write!(f, "{name}"),
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
@@ -33,6 +92,9 @@ pub struct Sensor {
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Debug, Default)]
 pub struct EditSensor {
+    /// Only sent when creating a sensor; the id of an existing sensor is part of the URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub desc: Option<String>,
@@ -46,6 +108,20 @@ pub struct EditSensor {
     pub attributes: Option<Vec<Attribute>>,
 }
 
+impl From<&Sensor> for EditSensor {
+    fn from(sensor: &Sensor) -> Self {
+        EditSensor {
+            id: None,
+            name: sensor.name.clone(),
+            desc: sensor.desc.clone(),
+            kind: sensor.kind.clone(),
+            uri: sensor.uri.clone(),
+            formula: sensor.formula.clone(),
+            attributes: sensor.attributes.clone(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, PartialEq, Debug, Store, Default)]
 pub struct Device {
     pub id: String,
@@ -62,6 +138,9 @@ pub struct Device {
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Debug, Store, Default)]
 pub struct EditDevice {
+    /// Only sent when creating a device; the id of an existing device is part of the URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub desc: Option<String>,
@@ -69,23 +148,88 @@ pub struct EditDevice {
     pub kind: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub uri: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Always sent, as `null` when cleared; the server keeps coordinates that are left out.
     pub lat: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub lon: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attributes: Option<Vec<Attribute>>,
 }
 
+impl From<&Device> for EditDevice {
+    fn from(device: &Device) -> Self {
+        EditDevice {
+            id: None,
+            name: device.name.clone(),
+            desc: device.desc.clone(),
+            kind: device.kind.clone(),
+            uri: device.uri.clone(),
+            lat: device.lat,
+            lon: device.lon,
+            attributes: device.attributes.clone(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
 pub struct RawData {
     pub id: String,
     #[serde(rename = "deviceId")]
     pub device_id: String,
     pub value: Vec<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub time: Option<Timestamp>,
+}
+
+/// A value written to a sensor through [`EndpointTrait::write_rawdata`].
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct EditRawData {
+    pub id: String,
+    pub value: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub time: Option<String>,
 }
 
+/// Time range of a historical raw data query. Times are ISO-8601, e.g. `2024-01-31T08:00:00Z`.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct RawDataQuery {
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub limit: Option<u32>,
+}
+
+impl RawDataQuery {
+    /// Renders the query as a URL query string, including the leading `?` when non-empty.
+    pub fn to_query_string(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(start) = &self.start {
+            params.push(format!("start={}", encode_query_value(start)));
+        }
+        if let Some(end) = &self.end {
+            params.push(format!("end={}", encode_query_value(end)));
+        }
+        if let Some(limit) = self.limit {
+            params.push(format!("limit={limit}"));
+        }
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", params.join("&"))
+        }
+    }
+}
+
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
 pub struct SensorWithData {
     pub sensor: Sensor,
@@ -117,16 +261,106 @@ impl fmt::Display for ActiveStatus {
     }
 }
 
+impl ActiveStatus {
+    /// Whether the device is considered down in this status.
+    pub fn is_outage(&self) -> bool {
+        matches!(self, ActiveStatus::Offline | ActiveStatus::Abnormal)
+    }
+}
+
+/// A span of time during which polling kept observing the same [`ActiveStatus`].
+///
+/// Times are Unix milliseconds. Gaps between segments mean nobody was watching the device.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StatusSegment {
+    pub status: ActiveStatus,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// Observations further apart than this are not joined into one segment.
+const STATUS_GAP_MS: i64 = 5 * 60 * 1000;
+
+/// Segments older than this are dropped when a new status is recorded.
+const STATUS_RETENTION_MS: i64 = 400 * 24 * 60 * 60 * 1000;
+
+/// Adds a status observed at `now` to a device's segments.
+pub fn record_status(segments: &mut Vec<StatusSegment>, status: ActiveStatus, now: i64) {
+    match segments.last_mut() {
+        Some(last) if now < last.end => {}
+        Some(last) if now - last.end <= STATUS_GAP_MS && last.status == status => last.end = now,
+        Some(last) if now - last.end <= STATUS_GAP_MS => {
+            let start = last.end;
+            segments.push(StatusSegment {
+                status,
+                start,
+                end: now,
+            });
+        }
+        _ => segments.push(StatusSegment {
+            status,
+            start: now,
+            end: now,
+        }),
+    }
+    segments.retain(|s| now - s.end <= STATUS_RETENTION_MS);
+}
+
+/// Availability of a device over a time window.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct UptimeReport {
+    /// Milliseconds spent in each monitored status.
+    pub online: i64,
+    pub outage: i64,
+    /// Milliseconds within the window with a recorded status.
+    pub observed: i64,
+    /// Number of times the device went from up (or unknown) to Offline/Abnormal.
+    pub outages: usize,
+}
+
+impl UptimeReport {
+    pub fn new(segments: &[StatusSegment], from: i64, to: i64) -> Self {
+        let mut report = UptimeReport::default();
+        let mut previous: Option<&StatusSegment> = None;
+        for segment in segments {
+            let start = segment.start.max(from);
+            let end = segment.end.min(to);
+            if end >= start {
+                let duration = end - start;
+                report.observed += duration;
+                if segment.status == ActiveStatus::Online {
+                    report.online += duration;
+                } else if segment.status.is_outage() {
+                    report.outage += duration;
+                }
+                let continues_outage = previous
+                    .is_some_and(|p| p.status.is_outage() && p.end >= segment.start);
+                if segment.status.is_outage() && !continues_outage && segment.start >= from {
+                    report.outages += 1;
+                }
+            }
+            previous = Some(segment);
+        }
+        report
+    }
+
+    /// Percentage of monitored time the device was online, if it was monitored at all.
+    pub fn uptime_percent(&self) -> Option<f64> {
+        let monitored = self.online + self.outage;
+        (monitored > 0).then(|| self.online as f64 * 100.0 / monitored as f64)
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug, Default)]
 pub struct ActiveInfo {
     #[serde(rename = "deviceId")]
     pub device_id: String,
     pub status: ActiveStatus,
     pub record: Option<i32>,
-    #[serde(rename = "lastDataTime")]
-    pub last_data_time: Option<String>,
-    #[serde(rename = "createTime")]
-    pub create_time: String,
+    #[serde(rename = "lastDataTime", default, deserialize_with = "lenient")]
+    pub last_data_time: Option<Timestamp>,
+    #[serde(rename = "createTime", default, deserialize_with = "lenient")]
+    pub create_time: Option<Timestamp>,
 }
 
 
@@ -141,8 +375,19 @@ pub struct ActiveDevice {
     pub min_uploads: Option<i32>,
     #[serde(rename = "maxUploads")]
     pub max_uploads: Option<i32>,
-    #[serde(rename = "createTime")]
-    pub create_time: Option<u64>,
+    #[serde(rename = "createTime", default, deserialize_with = "lenient")]
+    pub create_time: Option<Timestamp>,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct EditActiveDevice {
+    pub enable: bool,
+    pub period: String,
+    /// Always sent, as `null` when cleared; the server keeps limits that are left out.
+    #[serde(rename = "minUploads")]
+    pub min_uploads: Option<i32>,
+    #[serde(rename = "maxUploads")]
+    pub max_uploads: Option<i32>,
 }
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug, Default)]
@@ -163,8 +408,28 @@ pub struct ActiveNotify {
     #[serde(rename = "type")]
     pub kind: String,
     pub setting: ActiveNotifySetting,
-    #[serde(rename = "createTime")]
-    pub create_time: String,
+    #[serde(rename = "createTime", default, deserialize_with = "lenient")]
+    pub create_time: Option<Timestamp>,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct EditActiveNotify {
+    pub enable: bool,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub setting: ActiveNotifySetting,
+}
+
+impl From<&ActiveNotify> for EditActiveNotify {
+    fn from(notify: &ActiveNotify) -> Self {
+        EditActiveNotify {
+            enable: notify.enable,
+            name: notify.name.clone(),
+            kind: notify.kind.clone(),
+            setting: notify.setting.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
@@ -188,6 +453,20 @@ impl EndpointTrait for Endpoint {
         }
     }
 
+    fn write_rawdata(&self, device_id: &str) -> String {
+        match self {
+            Endpoint::General(endpoint) => endpoint.write_rawdata(device_id),
+            Endpoint::Edge(endpoint) => endpoint.write_rawdata(device_id),
+        }
+    }
+
+    fn sensor_rawdata(&self, device_id: &str, sensor_id: &str, query: &RawDataQuery) -> String {
+        match self {
+            Endpoint::General(endpoint) => endpoint.sensor_rawdata(device_id, sensor_id, query),
+            Endpoint::Edge(endpoint) => endpoint.sensor_rawdata(device_id, sensor_id, query),
+        }
+    }
+
     fn snapshot(&self, device_id: &str, sensor_id: &str, snapshot_id: &str) -> String {
         match self {
             Endpoint::General(endpoint) => endpoint.snapshot(device_id, sensor_id, snapshot_id),
@@ -195,6 +474,15 @@ impl EndpointTrait for Endpoint {
         }
     }
 
+    fn upload_snapshot(&self, device_id: &str, sensor_id: &str, snapshot_id: &str) -> String {
+        match self {
+            Endpoint::General(endpoint) => {
+                endpoint.upload_snapshot(device_id, sensor_id, snapshot_id)
+            }
+            Endpoint::Edge(endpoint) => endpoint.upload_snapshot(device_id, sensor_id, snapshot_id),
+        }
+    }
+
     fn baseurl(&self) -> String {
         match self {
             Endpoint::General(endpoint) => endpoint.baseurl(),
@@ -216,6 +504,34 @@ impl EndpointTrait for Endpoint {
         }
     }
 
+    fn create_device(&self) -> String {
+        match self {
+            Endpoint::General(endpoint) => endpoint.create_device(),
+            Endpoint::Edge(endpoint) => endpoint.create_device(),
+        }
+    }
+
+    fn delete_device(&self, device_id: &str) -> String {
+        match self {
+            Endpoint::General(endpoint) => endpoint.delete_device(device_id),
+            Endpoint::Edge(endpoint) => endpoint.delete_device(device_id),
+        }
+    }
+
+    fn create_sensor(&self, device_id: &str) -> String {
+        match self {
+            Endpoint::General(endpoint) => endpoint.create_sensor(device_id),
+            Endpoint::Edge(endpoint) => endpoint.create_sensor(device_id),
+        }
+    }
+
+    fn delete_sensor(&self, device_id: &str, sensor_id: &str) -> String {
+        match self {
+            Endpoint::General(endpoint) => endpoint.delete_sensor(device_id, sensor_id),
+            Endpoint::Edge(endpoint) => endpoint.delete_sensor(device_id, sensor_id),
+        }
+    }
+
     fn sensor(&self, device_id: &str, sensor_id: &str) -> String {
         match self {
             Endpoint::General(endpoint) => endpoint.sensor(device_id, sensor_id),
@@ -257,10 +573,18 @@ pub trait EndpointTrait {
     fn sensor(&self, device_id: &str, sensor_id: &str) -> String;
     fn metadata(&self) -> String;
     fn rawdata(&self, device_id: &str) -> String;
+    fn write_rawdata(&self, device_id: &str) -> String;
+    fn sensor_rawdata(&self, device_id: &str, sensor_id: &str, query: &RawDataQuery) -> String;
     fn snapshot(&self, device_id: &str, sensor_id: &str, snapshot_id: &str) -> String;
+    /// Where the file of a new snapshot is POSTed; the id is chosen by the uploader.
+    fn upload_snapshot(&self, device_id: &str, sensor_id: &str, snapshot_id: &str) -> String;
     fn baseurl(&self) -> String;
     fn kind(&self) -> String;
     fn device(&self, device_id: &str) -> String;
+    fn create_device(&self) -> String;
+    fn delete_device(&self, device_id: &str) -> String;
+    fn create_sensor(&self, device_id: &str) -> String;
+    fn delete_sensor(&self, device_id: &str, sensor_id: &str) -> String;
 }
 
 impl EndpointTrait for GeneralEndpoint {
@@ -270,12 +594,28 @@ impl EndpointTrait for GeneralEndpoint {
     fn rawdata(&self, device_id: &str) -> String {
         format!("{}/device/{device_id}/rawdata", self.base_url)
     }
+    fn write_rawdata(&self, device_id: &str) -> String {
+        format!("{}/device/{device_id}/rawdata", self.base_url)
+    }
+    fn sensor_rawdata(&self, device_id: &str, sensor_id: &str, query: &RawDataQuery) -> String {
+        format!(
+            "{}/device/{device_id}/sensor/{sensor_id}/rawdata{}",
+            self.base_url,
+            query.to_query_string()
+        )
+    }
     fn snapshot(&self, device_id: &str, sensor_id: &str, snapshot_id: &str) -> String {
         format!(
             "{}/device/{device_id}/sensor/{sensor_id}/snapshot/{snapshot_id}",
             self.base_url
         )
     }
+    fn upload_snapshot(&self, device_id: &str, sensor_id: &str, snapshot_id: &str) -> String {
+        format!(
+            "{}/device/{device_id}/sensor/{sensor_id}/snapshot/{snapshot_id}",
+            self.base_url
+        )
+    }
 
     fn baseurl(&self) -> String {
         self.base_url.to_owned()
@@ -289,6 +629,22 @@ impl EndpointTrait for GeneralEndpoint {
         format!("{}/device/{device_id}", self.base_url)
     }
 
+    fn create_device(&self) -> String {
+        format!("{}/device", self.base_url)
+    }
+
+    fn delete_device(&self, device_id: &str) -> String {
+        format!("{}/device/{device_id}", self.base_url)
+    }
+
+    fn create_sensor(&self, device_id: &str) -> String {
+        format!("{}/device/{device_id}/sensor", self.base_url)
+    }
+
+    fn delete_sensor(&self, device_id: &str, sensor_id: &str) -> String {
+        format!("{}/device/{device_id}/sensor/{sensor_id}", self.base_url)
+    }
+
     fn sensor(&self, device_id: &str, sensor_id: &str) -> String {
         format!("{}/device/{device_id}/sensor/{sensor_id}", self.base_url)
     }
@@ -324,6 +680,18 @@ impl EndpointTrait for EdgeEndpoint {
         format!("{}/device/{device_id}/rawdata", self.base_url)
     }
 
+    fn write_rawdata(&self, device_id: &str) -> String {
+        format!("{}/device/{device_id}/rawdata", self.base_url)
+    }
+
+    fn sensor_rawdata(&self, device_id: &str, sensor_id: &str, query: &RawDataQuery) -> String {
+        format!(
+            "{}/device/{device_id}/sensor/{sensor_id}/rawdata{}",
+            self.base_url,
+            query.to_query_string()
+        )
+    }
+
     fn snapshot(&self, device_id: &str, sensor_id: &str, snapshot_id: &str) -> String {
         format!(
             "{}/snapshot/device/{device_id}/sensor/{sensor_id}/snapshot/{snapshot_id}",
@@ -331,6 +699,13 @@ impl EndpointTrait for EdgeEndpoint {
         )
     }
 
+    fn upload_snapshot(&self, device_id: &str, sensor_id: &str, snapshot_id: &str) -> String {
+        format!(
+            "{}/snapshot/device/{device_id}/sensor/{sensor_id}/snapshot/{snapshot_id}",
+            self.base_url
+        )
+    }
+
     fn baseurl(&self) -> String {
         self.base_url.to_owned()
     }
@@ -343,6 +718,22 @@ impl EndpointTrait for EdgeEndpoint {
         format!("{}/device/{device_id}", self.base_url)
     }
 
+    fn create_device(&self) -> String {
+        format!("{}/device", self.base_url)
+    }
+
+    fn delete_device(&self, device_id: &str) -> String {
+        format!("{}/device/{device_id}", self.base_url)
+    }
+
+    fn create_sensor(&self, device_id: &str) -> String {
+        format!("{}/device/{device_id}/sensor", self.base_url)
+    }
+
+    fn delete_sensor(&self, device_id: &str, sensor_id: &str) -> String {
+        format!("{}/device/{device_id}/sensor/{sensor_id}", self.base_url)
+    }
+
     fn active_notify(&self, device_id: &str) -> String {
         format!("{}/device/{device_id}/active/notify", self.base_url)
     }
@@ -362,5 +753,216 @@ pub struct Project {
     pub endpoint_key: String,
 }
 
+/// Display preferences of the user, shared by all projects.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Preferences {
+    pub unit_system: UnitSystem,
+    /// Also show threshold alerts as browser notifications.
+    pub browser_notifications: bool,
+    pub time_zone: TimeZoneChoice,
+    /// `strftime` pattern of displayed times.
+    pub time_format: String,
+    /// Age in seconds after which a sensor value is stale, unless the device's monitor sets a
+    /// period.
+    pub stale_after_secs: u64,
+    /// Seconds between polls of live sensor values, keyed by project name; 0 turns polling off.
+    /// Projects without an entry use [`DEFAULT_POLL_SECS`].
+    pub poll_intervals: HashMap<String, u64>,
+}
+
+pub const DEFAULT_POLL_SECS: u64 = 10;
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            unit_system: UnitSystem::default(),
+            browser_notifications: false,
+            time_zone: TimeZoneChoice::default(),
+            time_format: DEFAULT_TIME_FORMAT.to_string(),
+            stale_after_secs: 15 * 60,
+            poll_intervals: HashMap::new(),
+        }
+    }
+}
+
+impl Preferences {
+    pub fn format_time(&self, time: &Timestamp) -> String {
+        time.display(self.time_zone, &self.time_format)
+    }
+
+    pub fn poll_interval(&self, project_name: &str) -> u64 {
+        self.poll_intervals
+            .get(project_name)
+            .copied()
+            .unwrap_or(DEFAULT_POLL_SECS)
+    }
+}
+
 pub type Endpoints = HashMap<String, Endpoint>;
 pub type Projects = HashMap<String, Project>;
+/// Recorded [`StatusSegment`]s keyed by [`status_key`].
+pub type StatusHistory = HashMap<String, Vec<StatusSegment>>;
+
+/// Key of a device in the [`StatusHistory`]. Device ids are only unique within a project, and
+/// never contain `/`.
+pub fn status_key(project_name: &str, device_id: &str) -> String {
+    format!("{project_name}/{device_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn cleared_device_fields_are_sent() {
+        let device = EditDevice {
+            name: "Boiler".to_string(),
+            kind: "heater".to_string(),
+            desc: Some(String::new()),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&device).unwrap(),
+            json!({"name": "Boiler", "type": "heater", "desc": "", "lat": null, "lon": null})
+        );
+    }
+
+    #[test]
+    fn cleared_upload_limits_are_sent() {
+        let setting = EditActiveDevice {
+            enable: true,
+            period: "5m".to_string(),
+            min_uploads: Some(1),
+            max_uploads: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&setting).unwrap(),
+            json!({"enable": true, "period": "5m", "minUploads": 1, "maxUploads": null})
+        );
+    }
+
+    const MINUTE: i64 = 60 * 1000;
+    const DAY: i64 = 24 * 60 * MINUTE;
+
+    fn segment(status: ActiveStatus, start: i64, end: i64) -> StatusSegment {
+        StatusSegment { status, start, end }
+    }
+
+    #[test]
+    fn status_within_gap_extends_the_segment() {
+        let mut segments = Vec::new();
+        record_status(&mut segments, ActiveStatus::Online, 0);
+        record_status(&mut segments, ActiveStatus::Online, STATUS_GAP_MS);
+        assert_eq!(segments, [segment(ActiveStatus::Online, 0, STATUS_GAP_MS)]);
+    }
+
+    #[test]
+    fn changed_status_within_gap_starts_where_the_last_ended() {
+        let mut segments = vec![segment(ActiveStatus::Online, 0, MINUTE)];
+        record_status(&mut segments, ActiveStatus::Offline, 3 * MINUTE);
+        assert_eq!(
+            segments,
+            [
+                segment(ActiveStatus::Online, 0, MINUTE),
+                segment(ActiveStatus::Offline, MINUTE, 3 * MINUTE),
+            ]
+        );
+    }
+
+    #[test]
+    fn status_after_gap_leaves_a_hole() {
+        let mut segments = vec![segment(ActiveStatus::Online, 0, MINUTE)];
+        let later = MINUTE + STATUS_GAP_MS + 1;
+        record_status(&mut segments, ActiveStatus::Online, later);
+        assert_eq!(
+            segments,
+            [
+                segment(ActiveStatus::Online, 0, MINUTE),
+                segment(ActiveStatus::Online, later, later),
+            ]
+        );
+    }
+
+    #[test]
+    fn status_older_than_the_last_is_ignored() {
+        let mut segments = vec![segment(ActiveStatus::Online, 0, 2 * MINUTE)];
+        record_status(&mut segments, ActiveStatus::Offline, MINUTE);
+        assert_eq!(segments, [segment(ActiveStatus::Online, 0, 2 * MINUTE)]);
+    }
+
+    #[test]
+    fn segments_past_retention_are_dropped() {
+        let now = 500 * DAY;
+        let mut segments = vec![
+            segment(ActiveStatus::Online, 0, now - 400 * DAY - 1),
+            segment(ActiveStatus::Offline, now - 400 * DAY - 1, now - 400 * DAY),
+        ];
+        record_status(&mut segments, ActiveStatus::Online, now);
+        assert_eq!(
+            segments,
+            [
+                segment(ActiveStatus::Offline, now - 400 * DAY - 1, now - 400 * DAY),
+                segment(ActiveStatus::Online, now, now),
+            ]
+        );
+    }
+
+    #[test]
+    fn status_segment_round_trips() {
+        let value = json!({"status": "abnormal", "start": 1, "end": 2});
+        let parsed: StatusSegment = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(parsed, segment(ActiveStatus::Abnormal, 1, 2));
+        assert_eq!(serde_json::to_value(parsed).unwrap(), value);
+    }
+
+    #[test]
+    fn uptime_is_clamped_to_the_window() {
+        let segments = [
+            segment(ActiveStatus::Online, 0, 60 * MINUTE),
+            segment(ActiveStatus::Offline, 60 * MINUTE, 90 * MINUTE),
+            segment(ActiveStatus::Stop, 90 * MINUTE, 120 * MINUTE),
+        ];
+        let report = UptimeReport::new(&segments, 30 * MINUTE, 100 * MINUTE);
+        assert_eq!(
+            report,
+            UptimeReport {
+                online: 30 * MINUTE,
+                outage: 30 * MINUTE,
+                observed: 70 * MINUTE,
+                outages: 1,
+            }
+        );
+        assert_eq!(report.uptime_percent(), Some(50.0));
+    }
+
+    #[test]
+    fn adjacent_outages_count_once() {
+        let segments = [
+            segment(ActiveStatus::Offline, 0, MINUTE),
+            segment(ActiveStatus::Abnormal, MINUTE, 2 * MINUTE),
+            segment(ActiveStatus::Online, 2 * MINUTE, 3 * MINUTE),
+            segment(ActiveStatus::Offline, 10 * MINUTE, 11 * MINUTE),
+        ];
+        assert_eq!(UptimeReport::new(&segments, 0, 20 * MINUTE).outages, 2);
+    }
+
+    #[test]
+    fn outages_begun_before_the_window_are_not_counted() {
+        let segments = [segment(ActiveStatus::Offline, 0, 10 * MINUTE)];
+        let report = UptimeReport::new(&segments, 5 * MINUTE, 20 * MINUTE);
+        assert_eq!(report.outages, 0);
+        assert_eq!(report.outage, 5 * MINUTE);
+    }
+
+    #[test]
+    fn unmonitored_window_has_no_uptime() {
+        let segments = [segment(ActiveStatus::Stop, 0, MINUTE)];
+        assert_eq!(
+            UptimeReport::new(&segments, 0, MINUTE).uptime_percent(),
+            None
+        );
+        assert_eq!(UptimeReport::new(&[], 0, MINUTE), UptimeReport::default());
+    }
+}
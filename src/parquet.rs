@@ -0,0 +1,609 @@
+const MAGIC: &[u8] = b"PAR1";
+
+// Physical types.
+const TYPE_INT64: i32 = 2;
+const TYPE_DOUBLE: i32 = 5;
+const TYPE_BYTE_ARRAY: i32 = 6;
+
+// Converted types, for readers that predate logical types.
+const CONVERTED_UTF8: i32 = 0;
+const CONVERTED_TIMESTAMP_MILLIS: i32 = 9;
+
+const REPETITION_OPTIONAL: i32 = 1;
+const ENCODING_PLAIN: i32 = 0;
+const ENCODING_RLE: i32 = 3;
+const CODEC_UNCOMPRESSED: i32 = 0;
+const PAGE_DATA: i32 = 0;
+
+/// Values of one column; `None` is a null.
+pub enum ColumnData {
+    Text(Vec<Option<String>>),
+    /// Milliseconds since the Unix epoch, in UTC.
+    TimestampMillis(Vec<Option<i64>>),
+    Double(Vec<Option<f64>>),
+}
+
+impl ColumnData {
+    fn len(&self) -> usize {
+        match self {
+            ColumnData::Text(values) => values.len(),
+            ColumnData::TimestampMillis(values) => values.len(),
+            ColumnData::Double(values) => values.len(),
+        }
+    }
+
+    fn physical_type(&self) -> i32 {
+        match self {
+            ColumnData::Text(_) => TYPE_BYTE_ARRAY,
+            ColumnData::TimestampMillis(_) => TYPE_INT64,
+            ColumnData::Double(_) => TYPE_DOUBLE,
+        }
+    }
+
+    /// Whether each row has a value, in order.
+    fn present(&self) -> Vec<bool> {
+        match self {
+            ColumnData::Text(values) => values.iter().map(Option::is_some).collect(),
+            ColumnData::TimestampMillis(values) => values.iter().map(Option::is_some).collect(),
+            ColumnData::Double(values) => values.iter().map(Option::is_some).collect(),
+        }
+    }
+
+    /// The non-null values in PLAIN encoding.
+    fn plain(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            ColumnData::Text(values) => {
+                for value in values.iter().flatten() {
+                    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                    out.extend_from_slice(value.as_bytes());
+                }
+            }
+            ColumnData::TimestampMillis(values) => {
+                for value in values.iter().flatten() {
+                    out.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            ColumnData::Double(values) => {
+                for value in values.iter().flatten() {
+                    out.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+        }
+        out
+    }
+}
+
+pub struct Column {
+    pub name: String,
+    pub data: ColumnData,
+}
+
+/// Encodes the columns, which must all have the same number of rows, as a Parquet file.
+///
+/// The file is uncompressed, with one row group and one PLAIN-encoded data page per column, and
+/// every column optional. That is all pandas, Polars or DuckDB need to read an export.
+pub fn write(columns: &[Column]) -> Vec<u8> {
+    let num_rows = columns.first().map_or(0, |c| c.data.len());
+    let mut out = MAGIC.to_vec();
+
+    // Data page of each column: its offset, size and value count.
+    let mut chunks = Vec::new();
+    if num_rows > 0 {
+        for column in columns {
+            let offset = out.len();
+            let body = page_body(&column.data);
+            let mut header = Compact::default();
+            header.i32(1, PAGE_DATA);
+            header.i32(2, body.len() as i32);
+            header.i32(3, body.len() as i32);
+            header.begin_struct(5);
+            header.i32(1, column.data.len() as i32);
+            header.i32(2, ENCODING_PLAIN);
+            header.i32(3, ENCODING_RLE);
+            header.i32(4, ENCODING_RLE);
+            header.end_struct();
+            header.stop();
+            out.extend_from_slice(&header.out);
+            out.extend_from_slice(&body);
+            chunks.push((offset, out.len() - offset));
+        }
+    }
+
+    let mut meta = Compact::default();
+    meta.i32(1, 1);
+    meta.list(2, TYPE_STRUCT, columns.len() + 1);
+    meta.begin_element();
+    meta.binary(4, b"schema");
+    meta.i32(5, columns.len() as i32);
+    meta.end_struct();
+    for column in columns {
+        meta.begin_element();
+        meta.i32(1, column.data.physical_type());
+        meta.i32(3, REPETITION_OPTIONAL);
+        meta.binary(4, column.name.as_bytes());
+        match column.data {
+            ColumnData::Text(_) => {
+                meta.i32(6, CONVERTED_UTF8);
+                meta.begin_struct(10);
+                meta.begin_struct(1);
+                meta.end_struct();
+                meta.end_struct();
+            }
+            ColumnData::TimestampMillis(_) => {
+                meta.i32(6, CONVERTED_TIMESTAMP_MILLIS);
+                meta.begin_struct(10);
+                meta.begin_struct(8);
+                meta.bool(1, true);
+                meta.begin_struct(2);
+                meta.begin_struct(1);
+                meta.end_struct();
+                meta.end_struct();
+                meta.end_struct();
+                meta.end_struct();
+            }
+            ColumnData::Double(_) => {}
+        }
+        meta.end_struct();
+    }
+    meta.i64(3, num_rows as i64);
+    meta.list(4, TYPE_STRUCT, usize::from(!chunks.is_empty()));
+    if !chunks.is_empty() {
+        meta.begin_element();
+        meta.list(1, TYPE_STRUCT, columns.len());
+        for (column, (offset, size)) in columns.iter().zip(&chunks) {
+            meta.begin_element();
+            meta.i64(2, *offset as i64);
+            meta.begin_struct(3);
+            meta.i32(1, column.data.physical_type());
+            meta.list(2, TYPE_I32, 2);
+            meta.raw_i32(ENCODING_PLAIN);
+            meta.raw_i32(ENCODING_RLE);
+            meta.list(3, TYPE_BINARY, 1);
+            meta.raw_binary(column.name.as_bytes());
+            meta.i32(4, CODEC_UNCOMPRESSED);
+            meta.i64(5, num_rows as i64);
+            meta.i64(6, *size as i64);
+            meta.i64(7, *size as i64);
+            meta.i64(9, *offset as i64);
+            meta.end_struct();
+            meta.end_struct();
+        }
+        let total: usize = chunks.iter().map(|(_, size)| size).sum();
+        meta.i64(2, total as i64);
+        meta.i64(3, num_rows as i64);
+        meta.end_struct();
+    }
+    meta.binary(6, b"data-viewer");
+    meta.stop();
+
+    out.extend_from_slice(&meta.out);
+    out.extend_from_slice(&(meta.out.len() as u32).to_le_bytes());
+    out.extend_from_slice(MAGIC);
+    out
+}
+
+/// Definition levels (1 for a value, 0 for a null) followed by the values.
+fn page_body(data: &ColumnData) -> Vec<u8> {
+    let mut levels = Vec::new();
+    let present = data.present();
+    let mut rest = present.as_slice();
+    // Runs of equal levels, each an RLE header and a one-byte level.
+    while let Some(&first) = rest.first() {
+        let run = rest.iter().take_while(|&&p| p == first).count();
+        write_varint(&mut levels, (run as u64) << 1);
+        levels.push(u8::from(first));
+        rest = &rest[run..];
+    }
+
+    let mut body = (levels.len() as u32).to_le_bytes().to_vec();
+    body.extend_from_slice(&levels);
+    body.extend_from_slice(&data.plain());
+    body
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+// Thrift compact protocol types.
+const TYPE_TRUE: u8 = 1;
+const TYPE_FALSE: u8 = 2;
+const TYPE_I32: u8 = 5;
+const TYPE_I64: u8 = 6;
+const TYPE_BINARY: u8 = 8;
+const TYPE_LIST: u8 = 9;
+const TYPE_STRUCT: u8 = 12;
+
+/// Writer of the Thrift compact protocol, which Parquet uses for page headers and metadata.
+#[derive(Default)]
+struct Compact {
+    out: Vec<u8>,
+    /// Id of the last field written in the current struct.
+    last_field: i16,
+    /// `last_field` of the enclosing structs.
+    parents: Vec<i16>,
+}
+
+impl Compact {
+    fn field(&mut self, id: i16, kind: u8) {
+        let delta = id - self.last_field;
+        if (1..=15).contains(&delta) {
+            self.out.push(((delta as u8) << 4) | kind);
+        } else {
+            self.out.push(kind);
+            write_varint(&mut self.out, zigzag(id.into()));
+        }
+        self.last_field = id;
+    }
+
+    fn bool(&mut self, id: i16, value: bool) {
+        self.field(id, if value { TYPE_TRUE } else { TYPE_FALSE });
+    }
+
+    fn i32(&mut self, id: i16, value: i32) {
+        self.field(id, TYPE_I32);
+        self.raw_i32(value);
+    }
+
+    fn i64(&mut self, id: i16, value: i64) {
+        self.field(id, TYPE_I64);
+        write_varint(&mut self.out, zigzag(value));
+    }
+
+    fn binary(&mut self, id: i16, value: &[u8]) {
+        self.field(id, TYPE_BINARY);
+        self.raw_binary(value);
+    }
+
+    fn raw_i32(&mut self, value: i32) {
+        write_varint(&mut self.out, zigzag(value.into()));
+    }
+
+    fn raw_binary(&mut self, value: &[u8]) {
+        write_varint(&mut self.out, value.len() as u64);
+        self.out.extend_from_slice(value);
+    }
+
+    /// Starts a list field; its elements follow, structs opened with [`Compact::begin_element`].
+    fn list(&mut self, id: i16, kind: u8, len: usize) {
+        self.field(id, TYPE_LIST);
+        if len < 15 {
+            self.out.push(((len as u8) << 4) | kind);
+        } else {
+            self.out.push(0xF0 | kind);
+            write_varint(&mut self.out, len as u64);
+        }
+    }
+
+    fn begin_struct(&mut self, id: i16) {
+        self.field(id, TYPE_STRUCT);
+        self.begin_element();
+    }
+
+    fn begin_element(&mut self) {
+        self.parents.push(self.last_field);
+        self.last_field = 0;
+    }
+
+    fn end_struct(&mut self) {
+        self.stop();
+        self.last_field = self.parents.pop().unwrap_or(0);
+    }
+
+    /// Ends the outermost struct.
+    fn stop(&mut self) {
+        self.out.push(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A decoded Thrift compact value, enough to read back what [`Compact`] writes.
+    #[derive(Clone, PartialEq, Debug)]
+    enum Value {
+        Bool(bool),
+        Int(i64),
+        Binary(Vec<u8>),
+        List(Vec<Value>),
+        Struct(Vec<(i16, Value)>),
+    }
+
+    impl Value {
+        fn field(&self, id: i16) -> &Value {
+            match self {
+                Value::Struct(fields) => {
+                    &fields
+                        .iter()
+                        .find(|(f, _)| *f == id)
+                        .expect("missing field")
+                        .1
+                }
+                _ => panic!("not a struct: {self:?}"),
+            }
+        }
+
+        fn int(&self) -> i64 {
+            match self {
+                Value::Int(n) => *n,
+                _ => panic!("not an integer: {self:?}"),
+            }
+        }
+
+        fn list(&self) -> &[Value] {
+            match self {
+                Value::List(items) => items,
+                _ => panic!("not a list: {self:?}"),
+            }
+        }
+    }
+
+    struct Reader<'a> {
+        bytes: &'a [u8],
+        pos: usize,
+    }
+
+    impl Reader<'_> {
+        fn byte(&mut self) -> u8 {
+            self.pos += 1;
+            self.bytes[self.pos - 1]
+        }
+
+        fn varint(&mut self) -> u64 {
+            let mut value = 0;
+            let mut shift = 0;
+            loop {
+                let byte = self.byte();
+                value |= u64::from(byte & 0x7F) << shift;
+                if byte < 0x80 {
+                    return value;
+                }
+                shift += 7;
+            }
+        }
+
+        fn zigzag(&mut self) -> i64 {
+            let n = self.varint();
+            (n >> 1) as i64 ^ -((n & 1) as i64)
+        }
+
+        fn value(&mut self, kind: u8) -> Value {
+            match kind {
+                TYPE_TRUE => Value::Bool(true),
+                TYPE_FALSE => Value::Bool(false),
+                TYPE_I32 | TYPE_I64 => Value::Int(self.zigzag()),
+                TYPE_BINARY => {
+                    let len = self.varint() as usize;
+                    self.pos += len;
+                    Value::Binary(self.bytes[self.pos - len..self.pos].to_vec())
+                }
+                TYPE_LIST => {
+                    let header = self.byte();
+                    let mut len = usize::from(header >> 4);
+                    if len == 15 {
+                        len = self.varint() as usize;
+                    }
+                    Value::List((0..len).map(|_| self.value(header & 0x0F)).collect())
+                }
+                TYPE_STRUCT => self.structure(),
+                _ => panic!("unexpected type {kind}"),
+            }
+        }
+
+        fn structure(&mut self) -> Value {
+            let mut fields = Vec::new();
+            let mut last = 0;
+            loop {
+                let header = self.byte();
+                if header == 0 {
+                    return Value::Struct(fields);
+                }
+                let id = match header >> 4 {
+                    0 => self.zigzag() as i16,
+                    delta => last + i16::from(delta),
+                };
+                last = id;
+                fields.push((id, self.value(header & 0x0F)));
+            }
+        }
+    }
+
+    fn read_value(bytes: &[u8]) -> (Value, usize) {
+        let mut reader = Reader { bytes, pos: 0 };
+        (reader.structure(), reader.pos)
+    }
+
+    #[test]
+    fn varint_and_zigzag() {
+        let mut out = Vec::new();
+        write_varint(&mut out, 1);
+        write_varint(&mut out, 300);
+        assert_eq!(out, [0x01, 0xAC, 0x02]);
+
+        assert_eq!(zigzag(0), 0);
+        assert_eq!(zigzag(-1), 1);
+        assert_eq!(zigzag(1), 2);
+        assert_eq!(zigzag(-2), 3);
+        assert_eq!(zigzag(i64::MIN), u64::MAX);
+    }
+
+    #[test]
+    fn field_headers_use_deltas() {
+        let mut compact = Compact::default();
+        compact.i32(1, 5);
+        compact.i32(3, -1);
+        // A jump over 15 ids and a step back need the long form.
+        compact.i32(20, 0);
+        compact.i32(4, 0);
+        compact.stop();
+        assert_eq!(
+            compact.out,
+            [0x15, 0x0A, 0x25, 0x01, 0x05, 40, 0x00, 0x05, 8, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn nested_structs_restore_the_field_id() {
+        let mut compact = Compact::default();
+        compact.i32(1, 1);
+        compact.begin_struct(2);
+        compact.bool(1, true);
+        compact.end_struct();
+        compact.i32(3, 2);
+        compact.stop();
+        assert_eq!(
+            compact.out,
+            [0x15, 0x02, 0x1C, 0x11, 0x00, 0x15, 0x04, 0x00]
+        );
+    }
+
+    #[test]
+    fn file_has_magic_and_footer_length() {
+        let file = write(&[Column {
+            name: "value".to_string(),
+            data: ColumnData::Double(vec![Some(1.0)]),
+        }]);
+        assert_eq!(&file[..4], MAGIC);
+        assert_eq!(&file[file.len() - 4..], MAGIC);
+
+        let footer_len =
+            u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap());
+        let footer_start = file.len() - 8 - footer_len as usize;
+        let (meta, read) = read_value(&file[footer_start..]);
+        assert_eq!(read, footer_len as usize);
+        assert_eq!(meta.field(3).int(), 1);
+    }
+
+    /// Reads back a column's values from its data page.
+    fn read_column(file: &[u8], chunk: &Value) -> Vec<Option<Vec<u8>>> {
+        let meta = chunk.field(3);
+        let offset = meta.field(9).int() as usize;
+        let (header, header_len) = read_value(&file[offset..]);
+        assert_eq!(header.field(1).int(), i64::from(PAGE_DATA));
+        let size = header.field(3).int() as usize;
+        assert_eq!(header_len + size, meta.field(7).int() as usize);
+        let num_values = header.field(5).field(1).int() as usize;
+
+        let body = &file[offset + header_len..offset + header_len + size];
+        let levels_len = u32::from_le_bytes(body[..4].try_into().unwrap()) as usize;
+        let mut reader = Reader {
+            bytes: &body[4..4 + levels_len],
+            pos: 0,
+        };
+        let mut levels = Vec::new();
+        while reader.pos < levels_len {
+            let run = reader.varint();
+            assert_eq!(run & 1, 0, "only RLE runs are written");
+            levels.extend(std::iter::repeat_n(reader.byte(), (run >> 1) as usize));
+        }
+        assert_eq!(levels.len(), num_values);
+
+        let mut values = &body[4 + levels_len..];
+        let physical_type = meta.field(1).int() as i32;
+        let mut out = Vec::new();
+        for level in levels {
+            if level == 0 {
+                out.push(None);
+                continue;
+            }
+            let len = if physical_type == TYPE_BYTE_ARRAY {
+                let len = u32::from_le_bytes(values[..4].try_into().unwrap()) as usize;
+                values = &values[4..];
+                len
+            } else {
+                8
+            };
+            out.push(Some(values[..len].to_vec()));
+            values = &values[len..];
+        }
+        assert!(values.is_empty());
+        out
+    }
+
+    #[test]
+    fn round_trip_with_nulls() {
+        let columns = [
+            Column {
+                name: "sensor_id".to_string(),
+                data: ColumnData::Text(vec![
+                    Some("t".to_string()),
+                    Some("h".to_string()),
+                    Some("t".to_string()),
+                ]),
+            },
+            Column {
+                name: "time".to_string(),
+                data: ColumnData::TimestampMillis(vec![Some(1_700_000_000_000), None, None]),
+            },
+            Column {
+                name: "value".to_string(),
+                data: ColumnData::Double(vec![None, Some(21.5), Some(-3.0)]),
+            },
+        ];
+        let file = write(&columns);
+
+        let footer_len =
+            u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap());
+        let (meta, _) = read_value(&file[file.len() - 8 - footer_len as usize..]);
+        assert_eq!(meta.field(3).int(), 3);
+
+        let schema = meta.field(2).list();
+        assert_eq!(schema.len(), 4);
+        assert_eq!(schema[0].field(5).int(), 3);
+        assert_eq!(*schema[1].field(4), Value::Binary(b"sensor_id".to_vec()));
+        assert_eq!(
+            schema[2].field(6).int(),
+            i64::from(CONVERTED_TIMESTAMP_MILLIS)
+        );
+        assert_eq!(*schema[2].field(10).field(8).field(1), Value::Bool(true));
+        assert_eq!(schema[3].field(1).int(), i64::from(TYPE_DOUBLE));
+
+        let row_groups = meta.field(4).list();
+        assert_eq!(row_groups.len(), 1);
+        let chunks = row_groups[0].field(1).list();
+        let text = |s: &str| Some(s.as_bytes().to_vec());
+        assert_eq!(
+            read_column(&file, &chunks[0]),
+            [text("t"), text("h"), text("t")]
+        );
+        assert_eq!(
+            read_column(&file, &chunks[1]),
+            [
+                Some(1_700_000_000_000i64.to_le_bytes().to_vec()),
+                None,
+                None
+            ]
+        );
+        assert_eq!(
+            read_column(&file, &chunks[2]),
+            [
+                None,
+                Some(21.5f64.to_le_bytes().to_vec()),
+                Some((-3.0f64).to_le_bytes().to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_table_has_no_row_groups() {
+        let file = write(&[Column {
+            name: "value".to_string(),
+            data: ColumnData::Double(Vec::new()),
+        }]);
+        let footer_len =
+            u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap());
+        let (meta, _) = read_value(&file[file.len() - 8 - footer_len as usize..]);
+        assert_eq!(meta.field(3).int(), 0);
+        assert!(meta.field(4).list().is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 use dioxus::prelude::*;
 use dioxus_sdk_storage::{use_synced_storage, LocalStorage};
 
-use crate::models::{Endpoints, Projects};
+use crate::models::{Endpoints, Preferences, Projects, StatusHistory};
 
 pub fn use_count_persistent() -> Signal<i32> {
     use_synced_storage::<LocalStorage, _>("count".to_string(), || 0)
@@ -14,3 +14,11 @@ pub fn use_endpoints_persistent() -> Signal<Endpoints> {
 pub fn use_project_persistence() -> Signal<Projects> {
     use_synced_storage::<LocalStorage, _>("projects".to_string(), || Projects::new())
 }
+
+pub fn use_status_history_persistent() -> Signal<StatusHistory> {
+    use_synced_storage::<LocalStorage, _>("status_history".to_string(), || StatusHistory::new())
+}
+
+pub fn use_preferences_persistent() -> Signal<Preferences> {
+    use_synced_storage::<LocalStorage, _>("preferences".to_string(), Preferences::default)
+}
@@ -0,0 +1,333 @@
+use std::collections::{HashMap, VecDeque};
+
+use base64::prelude::*;
+use dioxus::prelude::*;
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
+
+use crate::api::{ApiClient, ApiResult};
+
+/// Most snapshots kept per project page; room for a full snapshot gallery.
+const MAX_ENTRIES: usize = 256;
+/// Most image bytes kept per project page.
+const MAX_BYTES: usize = 64 * 1024 * 1024;
+/// Most evicted snapshots kept alive for the cards and galleries still showing them; like
+/// [`MAX_ENTRIES`], room for a full gallery.
+const MAX_RETIRED: usize = 256;
+
+/// Image type of a snapshot, from the `Content-Type` header or, failing that, its magic bytes.
+pub fn detect_mime(content_type: Option<&str>, bytes: &[u8]) -> &'static str {
+    let header = content_type
+        .and_then(|c| c.split(';').next())
+        .map(|c| c.trim().to_ascii_lowercase());
+    match header.as_deref() {
+        Some("image/jpeg" | "image/jpg") => return "image/jpeg",
+        Some("image/png") => return "image/png",
+        Some("image/webp") => return "image/webp",
+        Some("image/gif") => return "image/gif",
+        _ => {}
+    }
+    match bytes {
+        [0xFF, 0xD8, 0xFF, ..] => "image/jpeg",
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => "image/png",
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+/// File extension for a type returned by [`detect_mime`].
+pub fn mime_extension(mime: &str) -> &'static str {
+    match mime {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/webp" => "webp",
+        "image/gif" => "gif",
+        _ => "bin",
+    }
+}
+
+/// A snapshot ready to show in an `img`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CachedSnapshot {
+    /// An object URL on the web, a data URL elsewhere.
+    pub url: String,
+    pub mime: &'static str,
+    pub size: usize,
+}
+
+/// Device id, sensor id and snapshot id.
+pub type SnapshotKey = (String, String, String);
+
+/// A download shared by everyone asking for the same snapshot while it is in flight.
+type PendingSnapshot = Shared<LocalBoxFuture<'static, ApiResult<CachedSnapshot>>>;
+
+/// Snapshots already downloaded on this project page, evicting the least recently used ones
+/// past [`MAX_ENTRIES`] or [`MAX_BYTES`].
+///
+/// Every snapshot handed out by [`load_snapshot`] holds its URL until the caller calls
+/// [`SnapshotCache::release`], so an evicted object URL is only freed once nothing shows it.
+#[derive(Default)]
+pub struct SnapshotCache {
+    entries: HashMap<SnapshotKey, CachedSnapshot>,
+    /// Keys from least to most recently used.
+    order: VecDeque<SnapshotKey>,
+    bytes: usize,
+    /// Number of holders of each URL.
+    holds: HashMap<String, usize>,
+    /// URLs of evicted or replaced snapshots, oldest first, freed once they have no holder.
+    retired: VecDeque<String>,
+    pending: HashMap<SnapshotKey, PendingSnapshot>,
+}
+
+impl SnapshotCache {
+    fn get(&mut self, key: &SnapshotKey) -> Option<CachedSnapshot> {
+        let entry = self.entries.get(key)?.clone();
+        if let Some(i) = self.order.iter().position(|k| k == key) {
+            let key = self.order.remove(i)?;
+            self.order.push_back(key);
+        }
+        Some(entry)
+    }
+
+    fn hold(&mut self, url: &str) {
+        *self.holds.entry(url.to_string()).or_default() += 1;
+    }
+
+    /// Gives back a snapshot URL from [`load_snapshot`] that is no longer shown.
+    pub fn release(&mut self, url: &str) {
+        let Some(count) = self.holds.get_mut(url) else {
+            return;
+        };
+        *count -= 1;
+        if *count > 0 {
+            return;
+        }
+        self.holds.remove(url);
+        if let Some(i) = self.retired.iter().position(|u| u == url) {
+            self.retired.remove(i);
+            release_url(url);
+        }
+    }
+
+    /// Drops every snapshot, freeing their object URLs, including those of evicted snapshots.
+    pub fn clear(&mut self) {
+        for (_, snapshot) in self.entries.drain() {
+            release_url(&snapshot.url);
+        }
+        for url in self.retired.drain(..) {
+            release_url(&url);
+        }
+        self.holds.clear();
+        self.pending.clear();
+        self.order.clear();
+        self.bytes = 0;
+    }
+
+    fn insert(&mut self, key: SnapshotKey, snapshot: CachedSnapshot) {
+        // URLs retired by earlier inserts had time to get a holder; free those that didn't.
+        let holds = &self.holds;
+        self.retired.retain(|url| {
+            let held = holds.contains_key(url);
+            if !held {
+                release_url(url);
+            }
+            held
+        });
+        self.retired.retain(|url| *url != snapshot.url);
+
+        if let Some(old) = self.entries.insert(key.clone(), snapshot.clone()) {
+            self.bytes -= old.size;
+            self.order.retain(|k| *k != key);
+            if old.url != snapshot.url {
+                self.retired.push_back(old.url);
+            }
+        }
+        self.bytes += snapshot.size;
+        self.order.push_back(key);
+        // Always keep the newest entry, even if it alone is over the byte budget.
+        while self.order.len() > 1 && (self.order.len() > MAX_ENTRIES || self.bytes > MAX_BYTES) {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(evicted) = self.entries.remove(&oldest) {
+                self.bytes -= evicted.size;
+                self.retired.push_back(evicted.url);
+            }
+        }
+        // A holder that never releases must not keep images alive forever.
+        while self.retired.len() > MAX_RETIRED {
+            if let Some(url) = self.retired.pop_front() {
+                self.holds.remove(&url);
+                release_url(&url);
+            }
+        }
+    }
+}
+
+/// Creates an object URL for the image, so the page holds a short `blob:` URL instead of the
+/// whole image as base64.
+#[cfg(feature = "web")]
+async fn image_url(mime: &str, bytes: &[u8]) -> String {
+    let data = BASE64_STANDARD.encode(bytes);
+    let mut eval = document::eval(
+        r#"
+        const [mime, data] = await dioxus.recv();
+        const bytes = Uint8Array.from(atob(data), (c) => c.charCodeAt(0));
+        dioxus.send(URL.createObjectURL(new Blob([bytes], { type: mime })));
+        "#,
+    );
+    if eval.send((mime, &data)).is_ok() {
+        if let Ok(url) = eval.recv::<String>().await {
+            return url;
+        }
+    }
+    format!("data:{mime};base64,{data}")
+}
+
+#[cfg(not(feature = "web"))]
+async fn image_url(mime: &str, bytes: &[u8]) -> String {
+    format!("data:{mime};base64,{}", BASE64_STANDARD.encode(bytes))
+}
+
+/// Frees the image behind an object URL.
+fn release_url(url: &str) {
+    if url.starts_with("blob:") {
+        if let Ok(url) = serde_json::to_string(url) {
+            _ = document::eval(&format!("URL.revokeObjectURL({url});"));
+        }
+    }
+}
+
+/// The snapshot cache of the current project page.
+pub fn use_snapshot_cache() -> Signal<SnapshotCache> {
+    use_context::<Signal<SnapshotCache>>()
+}
+
+/// Returns a snapshot from the cache, downloading it first if needed, and holds its URL until
+/// the caller passes it to [`SnapshotCache::release`].
+pub async fn load_snapshot(
+    api: &ApiClient,
+    mut cache: Signal<SnapshotCache>,
+    device_id: &str,
+    sensor_id: &str,
+    snapshot_id: &str,
+) -> ApiResult<CachedSnapshot> {
+    let key = (
+        device_id.to_string(),
+        sensor_id.to_string(),
+        snapshot_id.to_string(),
+    );
+    let download = {
+        let mut cache = cache.write();
+        if let Some(snapshot) = cache.get(&key) {
+            cache.hold(&snapshot.url);
+            return Ok(snapshot);
+        }
+        cache
+            .pending
+            .entry(key.clone())
+            .or_insert_with(|| {
+                download_snapshot(api.clone(), key.clone())
+                    .boxed_local()
+                    .shared()
+            })
+            .clone()
+    };
+
+    let snapshot = download.clone().await;
+    let mut cache = cache.write();
+    if cache.pending.get(&key).is_some_and(|p| p.ptr_eq(&download)) {
+        cache.pending.remove(&key);
+    }
+    let snapshot = snapshot?;
+    // Every caller sharing the download gets here; the first one stores it.
+    if cache
+        .entries
+        .get(&key)
+        .is_none_or(|e| e.url != snapshot.url)
+    {
+        cache.insert(key, snapshot.clone());
+    }
+    cache.hold(&snapshot.url);
+    Ok(snapshot)
+}
+
+async fn download_snapshot(api: ApiClient, key: SnapshotKey) -> ApiResult<CachedSnapshot> {
+    let (device_id, sensor_id, snapshot_id) = &key;
+    let snapshot = api.snapshot(device_id, sensor_id, snapshot_id).await?;
+    let mime = detect_mime(snapshot.content_type.as_deref(), &snapshot.bytes);
+    Ok(CachedSnapshot {
+        url: image_url(mime, &snapshot.bytes).await,
+        mime,
+        size: snapshot.bytes.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(id: &str) -> SnapshotKey {
+        ("d".to_string(), "s".to_string(), id.to_string())
+    }
+
+    fn snapshot(url: &str, size: usize) -> CachedSnapshot {
+        CachedSnapshot {
+            url: format!("data:{url}"),
+            mime: "image/png",
+            size,
+        }
+    }
+
+    #[test]
+    fn evicted_urls_wait_for_their_holders() {
+        let mut cache = SnapshotCache::default();
+        cache.insert(key("a"), snapshot("a", MAX_BYTES));
+        cache.hold("data:a");
+        cache.insert(key("b"), snapshot("b", 1));
+        assert!(cache.get(&key("a")).is_none());
+        assert_eq!(cache.retired, ["data:a"]);
+
+        // Still shown, so a later insert keeps it.
+        cache.insert(key("c"), snapshot("c", 1));
+        assert_eq!(cache.retired, ["data:a"]);
+        cache.release("data:a");
+        assert!(cache.retired.is_empty());
+        assert!(cache.holds.is_empty());
+    }
+
+    #[test]
+    fn unheld_urls_are_freed_on_the_next_insert() {
+        let mut cache = SnapshotCache::default();
+        cache.insert(key("a"), snapshot("a", 1));
+        cache.insert(key("a"), snapshot("a2", 1));
+        assert_eq!(cache.retired, ["data:a"]);
+        assert_eq!(cache.bytes, 1);
+        cache.insert(key("b"), snapshot("b", 1));
+        assert!(cache.retired.is_empty());
+    }
+
+    #[test]
+    fn retired_urls_are_capped() {
+        let mut cache = SnapshotCache::default();
+        for i in 0..MAX_ENTRIES + MAX_RETIRED + 10 {
+            let url = format!("data:{i}");
+            cache.insert(key(&i.to_string()), snapshot(&i.to_string(), 1));
+            cache.hold(&url);
+        }
+        assert_eq!(cache.entries.len(), MAX_ENTRIES);
+        assert_eq!(cache.retired.len(), MAX_RETIRED);
+        assert_eq!(cache.holds.len(), MAX_ENTRIES + MAX_RETIRED);
+        assert_eq!(cache.retired.front().map(String::as_str), Some("data:10"));
+    }
+
+    #[test]
+    fn mime_from_header_or_bytes() {
+        assert_eq!(detect_mime(Some("image/JPG; q=1"), &[]), "image/jpeg");
+        assert_eq!(detect_mime(None, b"\x89PNG\r\n\x1a\n...."), "image/png");
+        assert_eq!(detect_mime(Some("text/plain"), b"GIF89a"), "image/gif");
+        assert_eq!(detect_mime(None, b"RIFF1234WEBPVP8 "), "image/webp");
+        assert_eq!(detect_mime(None, b"hello"), "application/octet-stream");
+        assert_eq!(mime_extension("image/jpeg"), "jpg");
+    }
+}
@@ -0,0 +1,317 @@
+use core::fmt;
+use std::time::Duration;
+
+use async_std::task::sleep;
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, FixedOffset, Local, NaiveDateTime, TimeZone, Utc,
+};
+use dioxus::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// Layouts of timestamps sent without a time zone, which are taken as UTC.
+const NAIVE_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"];
+
+/// Epoch numbers below this are seconds, above it milliseconds (year 5138 in seconds).
+const EPOCH_MILLIS_FROM: i64 = 100_000_000_000;
+
+/// A point in time reported by the API.
+///
+/// The API sends times as RFC 3339, as `2024-01-31 08:00:00` in UTC, or as Unix seconds or
+/// milliseconds; all are read into one type. It serializes as RFC 3339.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Timestamp(pub DateTime<Utc>);
+
+impl Timestamp {
+    pub fn parse(text: &str) -> Option<Timestamp> {
+        let text = text.trim();
+        if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+            return Some(Timestamp(time.with_timezone(&Utc)));
+        }
+        if let Ok(epoch) = text.parse::<i64>() {
+            return Timestamp::from_epoch(epoch);
+        }
+        NAIVE_FORMATS
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+            .map(|time| Timestamp(time.and_utc()))
+    }
+
+    /// Reads Unix seconds or milliseconds, telling them apart by magnitude.
+    pub fn from_epoch(epoch: i64) -> Option<Timestamp> {
+        let time = if epoch.abs() < EPOCH_MILLIS_FROM {
+            DateTime::from_timestamp(epoch, 0)
+        } else {
+            DateTime::from_timestamp_millis(epoch)
+        };
+        time.map(Timestamp)
+    }
+
+    pub fn millis(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
+
+    /// Formats the time in the given zone with a `strftime` pattern.
+    pub fn display(&self, zone: TimeZoneChoice, format: &str) -> String {
+        let format = if is_valid_time_format(format) {
+            format
+        } else {
+            DEFAULT_TIME_FORMAT
+        };
+        match zone {
+            TimeZoneChoice::Local => self.0.with_timezone(&Local).format(format).to_string(),
+            TimeZoneChoice::Utc => self.0.format(format).to_string(),
+            TimeZoneChoice::Offset(minutes) => match FixedOffset::east_opt(minutes * 60) {
+                Some(offset) => self.0.with_timezone(&offset).format(format).to_string(),
+                None => self.0.format(format).to_string(),
+            },
+        }
+    }
+
+    /// How long ago the time was, relative to `now`, e.g. `12 s ago`.
+    pub fn relative(&self, now: DateTime<Utc>) -> String {
+        let secs = (now - self.0).num_seconds();
+        let (amount, unit) = match secs.abs() {
+            0..5 => return "just now".to_string(),
+            s @ 5..60 => (s, "s"),
+            s @ 60..3600 => (s / 60, "min"),
+            s @ 3600..86400 => (s / 3600, "h"),
+            s => (s / 86400, "d"),
+        };
+        if secs < 0 {
+            format!("in {amount} {unit}")
+        } else {
+            format!("{amount} {unit} ago")
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.to_rfc3339())
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_rfc3339())
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        lenient(deserializer)?
+            .ok_or_else(|| serde::de::Error::custom("expected a timestamp"))
+    }
+}
+
+/// Deserializes an optional [`Timestamp`], reading values in an unknown layout as `None`
+/// instead of failing the whole response.
+pub fn lenient<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Timestamp>, D::Error> {
+    let time = match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(text)) => Timestamp::parse(&text),
+        Some(Value::Number(n)) => n.as_i64().and_then(Timestamp::from_epoch),
+        _ => None,
+    };
+    Ok(time)
+}
+
+pub const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Preset `strftime` patterns and their names.
+pub const TIME_FORMATS: [(&str, &str); 4] = [
+    (DEFAULT_TIME_FORMAT, "2024-01-31 08:00:00"),
+    ("%d/%m/%Y %H:%M:%S", "31/01/2024 08:00:00"),
+    ("%m/%d/%Y %I:%M:%S %p", "01/31/2024 08:00:00 AM"),
+    ("%b %e, %H:%M", "Jan 31, 08:00"),
+];
+
+/// Whether chrono can format with this pattern; formatting with an invalid one panics.
+pub fn is_valid_time_format(format: &str) -> bool {
+    !format.trim().is_empty() && !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+/// The time zone times are shown in, chosen by the user.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TimeZoneChoice {
+    /// The zone of the browser or the operating system.
+    #[default]
+    Local,
+    Utc,
+    /// A fixed offset east of UTC, in minutes.
+    Offset(i32),
+}
+
+impl TimeZoneChoice {
+    /// Parses `local`, `UTC` or an offset such as `+08:00` or `-0530`.
+    pub fn parse(text: &str) -> Option<TimeZoneChoice> {
+        let text = text.trim();
+        if text.eq_ignore_ascii_case("local") {
+            return Some(TimeZoneChoice::Local);
+        }
+        if text.eq_ignore_ascii_case("utc") || text.eq_ignore_ascii_case("z") {
+            return Some(TimeZoneChoice::Utc);
+        }
+        let (sign, rest) = match text.strip_prefix("UTC").unwrap_or(text) {
+            rest if rest.starts_with('+') => (1, &rest[1..]),
+            rest if rest.starts_with('-') => (-1, &rest[1..]),
+            _ => return None,
+        };
+        let digits: String = rest.chars().filter(|c| *c != ':').collect();
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let (hours, minutes) = match digits.len() {
+            1 | 2 => (digits.parse::<i32>().ok()?, 0),
+            4 => (digits[..2].parse::<i32>().ok()?, digits[2..].parse::<i32>().ok()?),
+            _ => return None,
+        };
+        if hours > 14 || minutes >= 60 {
+            return None;
+        }
+        Some(TimeZoneChoice::Offset(sign * (hours * 60 + minutes)))
+    }
+
+    /// The moment a wall-clock time in this zone stands for; `None` for a local time skipped
+    /// by a daylight saving change.
+    pub fn to_utc(&self, time: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            TimeZoneChoice::Local => Local
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+            TimeZoneChoice::Utc => Some(time.and_utc()),
+            TimeZoneChoice::Offset(minutes) => match FixedOffset::east_opt(minutes * 60) {
+                Some(offset) => offset
+                    .from_local_datetime(&time)
+                    .single()
+                    .map(|t| t.with_timezone(&Utc)),
+                None => Some(time.and_utc()),
+            },
+        }
+    }
+
+    /// The wall-clock time in this zone at a moment; the inverse of [`Self::to_utc`].
+    pub fn local_time(&self, time: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            TimeZoneChoice::Local => time.with_timezone(&Local).naive_local(),
+            TimeZoneChoice::Utc => time.naive_utc(),
+            TimeZoneChoice::Offset(minutes) => match FixedOffset::east_opt(minutes * 60) {
+                Some(offset) => time.with_timezone(&offset).naive_local(),
+                None => time.naive_utc(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for TimeZoneChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeZoneChoice::Local => write!(f, "local"),
+            TimeZoneChoice::Utc => write!(f, "UTC"),
+            TimeZoneChoice::Offset(minutes) => {
+                let sign = if *minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.abs();
+                write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+/// Provides the clock read by [`use_now`], ticking once a second for the whole app.
+pub fn use_clock_provider() {
+    let mut now = use_context_provider(|| Signal::new(Utc::now()));
+    use_future(move || async move {
+        loop {
+            sleep(Duration::from_secs(1)).await;
+            now.set(Utc::now());
+        }
+    });
+}
+
+/// The current time, refreshed every second so relative times stay current.
+pub fn use_now() -> ReadSignal<DateTime<Utc>> {
+    use_context::<Signal<DateTime<Utc>>>().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(text: &str) -> Timestamp {
+        let time = DateTime::parse_from_rfc3339(text).unwrap();
+        Timestamp(time.with_timezone(&Utc))
+    }
+
+    #[test]
+    fn epochs_are_seconds_or_milliseconds() {
+        let expected = Some(utc("2024-01-31T08:00:00Z"));
+        assert_eq!(Timestamp::parse("1706688000"), expected);
+        assert_eq!(Timestamp::parse(" 1706688000000 "), expected);
+        assert_eq!(
+            Timestamp::parse("1706688000123").map(|t| t.millis()),
+            Some(1_706_688_000_123)
+        );
+    }
+
+    #[test]
+    fn rfc3339_keeps_its_offset() {
+        assert_eq!(
+            Timestamp::parse("2024-01-31T16:00:00+08:00"),
+            Some(utc("2024-01-31T08:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn naive_times_are_utc() {
+        let expected = Some(utc("2024-01-31T08:00:00.5Z"));
+        assert_eq!(Timestamp::parse("2024-01-31 08:00:00.5"), expected);
+        assert_eq!(Timestamp::parse("2024-01-31T08:00:00.5"), expected);
+        assert_eq!(
+            Timestamp::parse("2024-01-31 08:00:00"),
+            Some(utc("2024-01-31T08:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn other_text_is_not_a_timestamp() {
+        assert_eq!(Timestamp::parse(""), None);
+        assert_eq!(Timestamp::parse("yesterday"), None);
+        assert_eq!(Timestamp::parse("2024-01-31"), None);
+    }
+
+    #[test]
+    fn time_zone_names_and_offsets_parse() {
+        for (text, zone) in [
+            (" Local ", TimeZoneChoice::Local),
+            ("utc", TimeZoneChoice::Utc),
+            ("Z", TimeZoneChoice::Utc),
+            ("+08:00", TimeZoneChoice::Offset(480)),
+            ("UTC-0530", TimeZoneChoice::Offset(-330)),
+            ("+9", TimeZoneChoice::Offset(540)),
+            ("+14:00", TimeZoneChoice::Offset(840)),
+        ] {
+            assert_eq!(TimeZoneChoice::parse(text), Some(zone), "{text}");
+        }
+    }
+
+    #[test]
+    fn invalid_time_zones_are_rejected() {
+        for text in ["", "08:00", "+15:00", "+08:60", "+080", "+ab", "Asia/Tokyo"] {
+            assert_eq!(TimeZoneChoice::parse(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn time_zones_round_trip_through_display() {
+        for zone in [
+            TimeZoneChoice::Local,
+            TimeZoneChoice::Utc,
+            TimeZoneChoice::Offset(-330),
+            TimeZoneChoice::Offset(480),
+        ] {
+            assert_eq!(TimeZoneChoice::parse(&zone.to_string()), Some(zone));
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::decode::DecodedValue;
+use crate::models::Attribute;
+
+/// Decimals shown when a sensor has no `precision` attribute; trailing zeros are trimmed.
+const DEFAULT_PRECISION: usize = 2;
+
+/// The unit system values are shown in, chosen by the user.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum UnitSystem {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+impl UnitSystem {
+    pub const ALL: [UnitSystem; 2] = [UnitSystem::Metric, UnitSystem::Imperial];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "metric",
+            UnitSystem::Imperial => "imperial",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<UnitSystem> {
+        UnitSystem::ALL.into_iter().find(|s| s.as_str() == name)
+    }
+}
+
+impl fmt::Display for UnitSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitSystem::Metric => write!(f, "Metric (°C, kPa, m, km/h)"),
+            UnitSystem::Imperial => write!(f, "Imperial (°F, psi, ft, mph)"),
+        }
+    }
+}
+
+/// A unit this viewer can convert, with the system it belongs to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Unit {
+    Celsius,
+    Fahrenheit,
+    Kilopascal,
+    Psi,
+    Metre,
+    Foot,
+    KilometresPerHour,
+    MilesPerHour,
+}
+
+impl Unit {
+    /// Recognizes the usual spellings of a unit attribute.
+    fn parse(unit: &str) -> Option<Unit> {
+        let unit = match unit.trim() {
+            "°C" | "℃" | "C" | "degC" => Unit::Celsius,
+            "°F" | "℉" | "F" | "degF" => Unit::Fahrenheit,
+            "kPa" | "kpa" => Unit::Kilopascal,
+            "psi" | "PSI" => Unit::Psi,
+            "m" => Unit::Metre,
+            "ft" => Unit::Foot,
+            "km/h" | "kmh" | "kph" => Unit::KilometresPerHour,
+            "mph" => Unit::MilesPerHour,
+            _ => return None,
+        };
+        Some(unit)
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Unit::Celsius => "°C",
+            Unit::Fahrenheit => "°F",
+            Unit::Kilopascal => "kPa",
+            Unit::Psi => "psi",
+            Unit::Metre => "m",
+            Unit::Foot => "ft",
+            Unit::KilometresPerHour => "km/h",
+            Unit::MilesPerHour => "mph",
+        }
+    }
+
+    /// The unit measuring the same quantity in `system`.
+    fn in_system(self, system: UnitSystem) -> Unit {
+        match (self, system) {
+            (Unit::Celsius | Unit::Fahrenheit, UnitSystem::Metric) => Unit::Celsius,
+            (Unit::Celsius | Unit::Fahrenheit, UnitSystem::Imperial) => Unit::Fahrenheit,
+            (Unit::Kilopascal | Unit::Psi, UnitSystem::Metric) => Unit::Kilopascal,
+            (Unit::Kilopascal | Unit::Psi, UnitSystem::Imperial) => Unit::Psi,
+            (Unit::Metre | Unit::Foot, UnitSystem::Metric) => Unit::Metre,
+            (Unit::Metre | Unit::Foot, UnitSystem::Imperial) => Unit::Foot,
+            (Unit::KilometresPerHour | Unit::MilesPerHour, UnitSystem::Metric) => {
+                Unit::KilometresPerHour
+            }
+            (Unit::KilometresPerHour | Unit::MilesPerHour, UnitSystem::Imperial) => {
+                Unit::MilesPerHour
+            }
+        }
+    }
+
+    fn convert(self, to: Unit, value: f64) -> f64 {
+        match (self, to) {
+            (Unit::Celsius, Unit::Fahrenheit) => value * 9.0 / 5.0 + 32.0,
+            (Unit::Fahrenheit, Unit::Celsius) => (value - 32.0) * 5.0 / 9.0,
+            (Unit::Kilopascal, Unit::Psi) => value / 6.894_757,
+            (Unit::Psi, Unit::Kilopascal) => value * 6.894_757,
+            (Unit::Metre, Unit::Foot) => value / 0.3048,
+            (Unit::Foot, Unit::Metre) => value * 0.3048,
+            (Unit::KilometresPerHour, Unit::MilesPerHour) => value / 1.609_344,
+            (Unit::MilesPerHour, Unit::KilometresPerHour) => value * 1.609_344,
+            _ => value,
+        }
+    }
+}
+
+/// How to show a sensor's values, read from its `unit`, `precision`, `min`, `max` and `label`
+/// attributes and converted to the user's [`UnitSystem`].
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct DisplayFormat {
+    /// The unit shown after values, after conversion.
+    pub unit: Option<String>,
+    pub precision: Option<usize>,
+    /// Expected range of the values, after conversion.
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Name to show instead of the sensor's name.
+    pub label: Option<String>,
+    /// Source and target unit when the values need converting.
+    conversion: Option<(Unit, Unit)>,
+}
+
+impl DisplayFormat {
+    /// Reads the format from a sensor's attributes; unparsable numbers are ignored.
+    pub fn new(attributes: &[Attribute], system: UnitSystem) -> Self {
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|a| a.key.eq_ignore_ascii_case(key))
+                .map(|a| a.value.trim())
+                .filter(|v| !v.is_empty())
+        };
+        let number = |key: &str| attribute(key)?.parse::<f64>().ok().filter(|n| n.is_finite());
+
+        let conversion = attribute("unit")
+            .and_then(Unit::parse)
+            .map(|unit| (unit, unit.in_system(system)))
+            .filter(|(from, to)| from != to);
+        let unit = match conversion {
+            Some((_, to)) => Some(to.symbol().to_string()),
+            None => attribute("unit").map(str::to_string),
+        };
+
+        let mut format = DisplayFormat {
+            unit,
+            precision: attribute("precision").and_then(|p| p.parse().ok()),
+            min: None,
+            max: None,
+            label: attribute("label").map(str::to_string),
+            conversion,
+        };
+        format.min = number("min").map(|n| format.convert(n));
+        format.max = number("max").map(|n| format.convert(n));
+        format
+    }
+
+    /// Converts a value from the sensor's unit to the displayed one.
+    pub fn convert(&self, value: f64) -> f64 {
+        match self.conversion {
+            Some((from, to)) => from.convert(to, value),
+            None => value,
+        }
+    }
+
+    /// Formats an already converted number with the sensor's precision, without the unit.
+    pub fn format_number(&self, value: f64) -> String {
+        match self.precision {
+            Some(decimals) => format!("{value:.decimals$}"),
+            None => {
+                let text = format!("{value:.DEFAULT_PRECISION$}");
+                let text = text.trim_end_matches('0').trim_end_matches('.');
+                match text {
+                    "-0" => "0".to_string(),
+                    text => text.to_string(),
+                }
+            }
+        }
+    }
+
+    /// Converts and formats a raw number, followed by the unit.
+    pub fn format_value(&self, value: f64) -> String {
+        let number = self.format_number(self.convert(value));
+        match &self.unit {
+            Some(unit) => format!("{number} {unit}"),
+            None => number,
+        }
+    }
+
+    /// Formats a decoded value; only numbers are converted and rounded.
+    pub fn format(&self, value: &DecodedValue) -> String {
+        match value {
+            DecodedValue::Number(n) => self.format_value(*n),
+            DecodedValue::Multi(values) => values
+                .iter()
+                .map(|v| self.format(v))
+                .collect::<Vec<_>>()
+                .join(", "),
+            value => value.to_string(),
+        }
+    }
+}
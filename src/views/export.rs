@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{use_toast, ToastOptions};
+
+use crate::api::use_api;
+use crate::components::button::{Button, ButtonVariant};
+use crate::components::dialog::{DialogContent, DialogDescription, DialogRoot, DialogTitle};
+use crate::components::input::Input;
+use crate::components::label::Label;
+use crate::components::radio_group::{RadioGroup, RadioItem};
+use crate::export::{encode, save_export, ExportFormat, ExportRow};
+use crate::models::{Device, Preferences, RawDataQuery, Sensor};
+
+use super::sensor::datetime_input_to_iso;
+
+/// Button and dialog exporting the raw data of some sensors of a device in a time range.
+#[component]
+pub fn ExportDialog(
+    device: ReadSignal<Device>,
+    /// Sensors offered for export.
+    sensors: ReadSignal<Vec<Sensor>>,
+    /// Ids of the sensors checked when the dialog opens.
+    selected: ReadSignal<Vec<String>>,
+) -> Element {
+    let api = use_api();
+    let preferences = use_context::<Signal<Preferences>>();
+    let mut open = use_signal(|| false);
+    let mut start = use_signal(String::new);
+    let mut end = use_signal(String::new);
+    let mut limit = use_signal(String::new);
+    let mut format = use_signal(ExportFormat::default);
+    let mut checked = use_signal(Vec::<String>::new);
+    // Sensors fetched so far and in total, while exporting.
+    let mut progress = use_signal(|| None::<(usize, usize)>);
+
+    let open_dialog = move |_| {
+        checked.set(selected());
+        open.set(true);
+    };
+
+    let mut toggle = move |id: String| {
+        let mut checked = checked.write();
+        if let Some(i) = checked.iter().position(|c| *c == id) {
+            checked.remove(i);
+        } else {
+            checked.push(id);
+        }
+    };
+
+    let on_export = move |_| async move {
+        let Some(api) = api() else {
+            return;
+        };
+        let toastapi = use_toast();
+        let zone = preferences().time_zone;
+        let query = RawDataQuery {
+            start: datetime_input_to_iso(&start(), zone),
+            end: datetime_input_to_iso(&end(), zone),
+            limit: limit().trim().parse().ok(),
+        };
+        let targets: Vec<Sensor> = sensors()
+            .into_iter()
+            .filter(|s| checked().contains(&s.id))
+            .collect();
+
+        let mut rows = Vec::new();
+        for (i, sensor) in targets.iter().enumerate() {
+            progress.set(Some((i, targets.len())));
+            match api.sensor_rawdata(&device().id, &sensor.id, &query).await {
+                Ok(data) => rows.extend(data.iter().map(|d| ExportRow::new(sensor, d))),
+                Err(e) => {
+                    progress.set(None);
+                    toastapi.error(
+                        format!("Export of sensor '{}' failed", sensor.id),
+                        ToastOptions::new()
+                            .description(format!("{e}"))
+                            .duration(Duration::from_secs(10)),
+                    );
+                    return;
+                }
+            }
+        }
+        progress.set(None);
+
+        if rows.is_empty() {
+            toastapi.warning(
+                "Nothing to export".to_string(),
+                ToastOptions::new()
+                    .description("No data in this range".to_string())
+                    .duration(Duration::from_secs(5)),
+            );
+            return;
+        }
+        rows.sort_by(|a, b| (a.time, &a.sensor_id).cmp(&(b.time, &b.sensor_id)));
+
+        let format = format();
+        let file_name = format!(
+            "{}-{}.{}",
+            device().id,
+            Utc::now().format("%Y%m%d-%H%M%S"),
+            format.as_str()
+        );
+        let saved = match encode(format, &rows) {
+            Ok(bytes) => save_export(&file_name, format, &bytes).await,
+            Err(e) => Err(format!("{e}")),
+        };
+        match saved {
+            Ok(true) => {
+                toastapi.success(
+                    format!("Exported {} rows", rows.len()),
+                    ToastOptions::new().duration(Duration::from_secs(5)),
+                );
+                open.set(false);
+            }
+            Ok(false) => {}
+            Err(e) => {
+                toastapi.error(
+                    "Export Failed".to_string(),
+                    ToastOptions::new()
+                        .description(e)
+                        .duration(Duration::from_secs(10)),
+                );
+            }
+        }
+    };
+
+    rsx! {
+        Button { variant: ButtonVariant::Outline, onclick: open_dialog, "Export" }
+        DialogRoot { open: open(), on_open_change: move |v| open.set(v),
+            DialogContent {
+                button {
+                    class: "dialog-close",
+                    r#type: "button",
+                    aria_label: "Close",
+                    tabindex: if open() { "0" } else { "-1" },
+                    onclick: move |_| open.set(false),
+                    "×"
+                }
+                DialogTitle { "Export {device().name}" }
+                DialogDescription {
+                    "Raw and formula-applied values of the checked sensors. Start and end use the time zone from Settings ({preferences().time_zone})."
+                }
+                div { class: "flex flex-col gap-4",
+                    div { class: "flex flex-wrap gap-4",
+                        div { class: "flex flex-col gap-2",
+                            Label { html_for: "export_start", "Start" }
+                            Input {
+                                id: "export_start",
+                                r#type: "datetime-local",
+                                value: start(),
+                                oninput: move |e: FormEvent| start.set(e.value()),
+                            }
+                        }
+                        div { class: "flex flex-col gap-2",
+                            Label { html_for: "export_end", "End" }
+                            Input {
+                                id: "export_end",
+                                r#type: "datetime-local",
+                                value: end(),
+                                oninput: move |e: FormEvent| end.set(e.value()),
+                            }
+                        }
+                        div { class: "flex flex-col gap-2",
+                            Label { html_for: "export_limit", "Limit per sensor" }
+                            Input {
+                                id: "export_limit",
+                                r#type: "number",
+                                min: "1",
+                                placeholder: "Server default",
+                                value: limit(),
+                                oninput: move |e: FormEvent| limit.set(e.value()),
+                            }
+                        }
+                    }
+
+                    div { class: "flex flex-col gap-2",
+                        p { class: "text-sm font-medium", "Sensors" }
+                        for sensor in sensors() {
+                            label {
+                                key: "{sensor.id}",
+                                class: "flex items-center gap-2",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: checked().contains(&sensor.id),
+                                    onchange: {
+                                        let id = sensor.id.clone();
+                                        move |_| toggle(id.clone())
+                                    },
+                                }
+                                "{sensor.name} ({sensor.id})"
+                            }
+                        }
+                    }
+
+                    div { class: "flex flex-col gap-2",
+                        Label { html_for: "export_format", "Format" }
+                        RadioGroup {
+                            id: "export_format",
+                            value: format().as_str().to_string(),
+                            horizontal: true,
+                            on_value_change: move |v: String| {
+                                if let Some(f) = ExportFormat::from_name(&v) {
+                                    format.set(f);
+                                }
+                            },
+                            for (i , f) in ExportFormat::ALL.into_iter().enumerate() {
+                                RadioItem { index: i, value: f.as_str().to_string(), "{f}" }
+                            }
+                        }
+                    }
+
+                    div { class: "flex flex-row-reverse items-center gap-4",
+                        Button {
+                            disabled: checked().is_empty() || progress().is_some(),
+                            onclick: on_export,
+                            "Export"
+                        }
+                        if let Some((done, total)) = progress() {
+                            span { class: "text-sm", "Fetching sensor {done + 1} of {total}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
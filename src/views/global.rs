@@ -1,6 +1,11 @@
 use dioxus::prelude::*;
+use reqwest::Client;
 
-use crate::persistence::{use_endpoints_persistent, use_project_persistence};
+use crate::persistence::{
+    use_endpoints_persistent, use_preferences_persistent, use_project_persistence,
+    use_status_history_persistent,
+};
+use crate::timestamp::use_clock_provider;
 
 
 
@@ -10,6 +15,13 @@ pub fn Providers(children: Element) -> Element {
     use_context_provider(|| endpoints);
     let projects = use_project_persistence();
     use_context_provider(|| projects);
+    let status_history = use_status_history_persistent();
+    use_context_provider(|| status_history);
+    let preferences = use_preferences_persistent();
+    use_context_provider(|| preferences);
+    // A single HTTP client so every project page shares one connection pool.
+    use_context_provider(Client::new);
+    use_clock_provider();
     
     children
 }
\ No newline at end of file
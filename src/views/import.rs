@@ -0,0 +1,295 @@
+use std::time::Duration;
+
+use dioxus::html::FileData;
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{use_toast, ToastOptions};
+
+use crate::api::{use_api, ApiError, ApiResult};
+use crate::components::button::{Button, ButtonVariant};
+use crate::components::dialog::{DialogContent, DialogDescription, DialogRoot, DialogTitle};
+use crate::import::{parse_import, plan_import, ImportAction, ImportStep};
+use crate::models::Device;
+
+/// Progress of one step while applying an import.
+#[derive(Clone, PartialEq, Debug)]
+enum StepStatus {
+    Pending,
+    Running,
+    Done,
+    Failed(String),
+}
+
+fn action_class(action: ImportAction) -> &'static str {
+    match action {
+        ImportAction::Create => "bg-green-600",
+        ImportAction::Update => "bg-amber-500",
+        ImportAction::Skip => "bg-gray-400",
+    }
+}
+
+/// Button and wizard importing devices and sensors from a CSV or JSON file: pick a file,
+/// review the dry run, then apply it step by step.
+#[component]
+pub fn ImportDialog(project_meta: Resource<ApiResult<Vec<Device>>>) -> Element {
+    let api = use_api();
+    let mut open = use_signal(|| false);
+    let mut file_name = use_signal(String::new);
+    let mut loading = use_signal(|| false);
+    let mut errors = use_signal(Vec::<String>::new);
+    let mut steps = use_signal(Vec::<ImportStep>::new);
+    let mut statuses = use_signal(Vec::<StepStatus>::new);
+    let mut applying = use_signal(|| false);
+
+    let mut reset = move || {
+        file_name.set(String::new());
+        errors.set(Vec::new());
+        steps.set(Vec::new());
+        statuses.set(Vec::new());
+    };
+
+    // Reads the file and compares it with the current metadata.
+    let load = move |file: FileData| async move {
+        reset();
+        file_name.set(file.name());
+        loading.set(true);
+        let result = async {
+            let text = file.read_string().await.map_err(|e| vec![format!("{e}")])?;
+            let patches = parse_import(&file.name(), &text)?;
+            let api = api().ok_or_else(|| vec![ApiError::NotConfigured.summary()])?;
+            let metadata = api.metadata().await.map_err(|e| vec![format!("{e}")])?;
+            plan_import(&metadata.devices, &patches)
+        }
+        .await;
+        loading.set(false);
+        match result {
+            Ok(plan) => {
+                statuses.set(vec![StepStatus::Pending; plan.len()]);
+                steps.set(plan);
+            }
+            Err(e) => errors.set(e),
+        }
+    };
+
+    // Applies the steps that are pending, or with `retry` the ones that failed, in file order.
+    let apply = move |retry: bool| async move {
+        let Some(api) = api() else {
+            return;
+        };
+        applying.set(true);
+        let plan = steps();
+        for (i, step) in plan.iter().enumerate() {
+            let runs = match &statuses.peek()[i] {
+                StepStatus::Pending => !retry,
+                StepStatus::Failed(_) => retry,
+                _ => false,
+            };
+            if !runs {
+                continue;
+            }
+            statuses.write()[i] = StepStatus::Running;
+            statuses.write()[i] = match step.apply(&api).await {
+                Ok(()) => StepStatus::Done,
+                Err(e) => StepStatus::Failed(format!("{e}")),
+            };
+        }
+        applying.set(false);
+        project_meta.restart();
+
+        let failed = statuses
+            .peek()
+            .iter()
+            .filter(|s| matches!(s, StepStatus::Failed(_)))
+            .count();
+        let toastapi = use_toast();
+        if failed == 0 {
+            toastapi.success(
+                "Import finished".to_string(),
+                ToastOptions::new().duration(Duration::from_secs(5)),
+            );
+        } else {
+            toastapi.error(
+                "Import incomplete".to_string(),
+                ToastOptions::new()
+                    .description(format!("{failed} step(s) failed; they can be retried"))
+                    .duration(Duration::from_secs(10)),
+            );
+        }
+    };
+
+    let count = move |action: ImportAction| steps().iter().filter(|s| s.action == action).count();
+    let started = use_memo(move || statuses().iter().any(|s| *s != StepStatus::Pending));
+    let finished = use_memo(move || {
+        steps()
+            .iter()
+            .zip(statuses())
+            .filter(|(step, status)| {
+                step.action != ImportAction::Skip
+                    && matches!(status, StepStatus::Done | StepStatus::Failed(_))
+            })
+            .count()
+    });
+    let to_apply = use_memo(move || {
+        steps()
+            .iter()
+            .filter(|s| s.action != ImportAction::Skip)
+            .count()
+    });
+    let failures: Memo<Vec<(String, String)>> = use_memo(move || {
+        steps()
+            .iter()
+            .zip(statuses())
+            .filter_map(|(step, status)| match status {
+                StepStatus::Failed(e) => Some((step.target(), e)),
+                _ => None,
+            })
+            .collect()
+    });
+
+    let body = if loading() {
+        rsx! {
+            p { "Reading {file_name} and comparing it with the project..." }
+        }
+    } else if !errors().is_empty() {
+        rsx! {
+            div { class: "flex flex-col gap-2",
+                p { class: "font-medium text-red-500", "{file_name} cannot be imported" }
+                ul { class: "list-disc pl-6 text-sm text-red-500 max-h-64 overflow-auto",
+                    for error in errors() {
+                        li { "{error}" }
+                    }
+                }
+                Button { variant: ButtonVariant::Outline, onclick: move |_| reset(), "Choose another file" }
+            }
+        }
+    } else if !steps().is_empty() {
+        rsx! {
+            div { class: "flex flex-col gap-4",
+                p { class: "text-sm",
+                    "{file_name}: {count(ImportAction::Create)} to create, {count(ImportAction::Update)} to update, {count(ImportAction::Skip)} unchanged"
+                }
+                if started() {
+                    div { class: "h-2 w-full rounded bg-gray-200",
+                        div {
+                            class: "h-2 rounded bg-blue-500 transition-all",
+                            style: "width: {finished() * 100 / to_apply().max(1)}%",
+                        }
+                    }
+                    p { class: "text-sm", "{finished()} of {to_apply()} processed" }
+                }
+                div { class: "max-h-80 overflow-auto",
+                    table { class: "w-full text-left text-sm",
+                        thead {
+                            tr {
+                                th { class: "pr-4", "Action" }
+                                th { class: "pr-4", "Target" }
+                                th { class: "pr-4", "Changes" }
+                                th { "Status" }
+                            }
+                        }
+                        tbody {
+                            for (i , step) in steps().into_iter().enumerate() {
+                                tr { class: "border-t",
+                                    td { class: "py-1 pr-4",
+                                        span { class: "px-2 rounded text-white {action_class(step.action)}",
+                                            "{step.action}"
+                                        }
+                                    }
+                                    td { class: "py-1 pr-4 break-all", "{step.target()}" }
+                                    td { class: "py-1 pr-4", {step.changes.join(", ")} }
+                                    td { class: "py-1",
+                                        match statuses().get(i).cloned() {
+                                            Some(StepStatus::Running) => rsx! { "Applying..." },
+                                            Some(StepStatus::Done) => rsx! { "Done" },
+                                            Some(StepStatus::Failed(e)) => rsx! {
+                                                span { class: "text-red-500", title: "{e}", "Failed" }
+                                            },
+                                            _ => rsx! {},
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                if !failures().is_empty() && !applying() {
+                    div { class: "flex flex-col gap-2",
+                        p { class: "font-medium text-red-500", "Errors" }
+                        ul { class: "list-disc pl-6 text-sm text-red-500 max-h-40 overflow-auto",
+                            for (target , error) in failures() {
+                                li { "{target}: {error}" }
+                            }
+                        }
+                    }
+                }
+                div { class: "flex flex-row-reverse gap-4",
+                    if !started() {
+                        Button {
+                            disabled: to_apply() == 0 || applying(),
+                            onclick: move |_| apply(false),
+                            "Apply {to_apply()} changes"
+                        }
+                    } else if !failures().is_empty() {
+                        Button {
+                            disabled: applying(),
+                            onclick: move |_| apply(true),
+                            "Retry failed"
+                        }
+                    }
+                    Button {
+                        variant: ButtonVariant::Outline,
+                        disabled: applying(),
+                        onclick: move |_| reset(),
+                        "Choose another file"
+                    }
+                }
+            }
+        }
+    } else {
+        rsx! {
+            div { class: "flex flex-col gap-2 text-sm",
+                p {
+                    "A JSON array of devices, each with id, name, type, desc, uri, lat, lon, attributes and sensors; sensors have id, name, type, desc, uri, formula and attributes."
+                }
+                p {
+                    "Or a CSV file with one row per sensor and the columns device_id, device_name, device_type, device_desc, device_uri, lat, lon, device_attributes, sensor_id, sensor_name, sensor_type, sensor_desc, sensor_uri, formula and sensor_attributes. Attributes are written key=value;key=value."
+                }
+                p { "Empty fields keep the current value. Nothing is changed before you apply the dry run." }
+                input {
+                    r#type: "file",
+                    accept: ".csv,.json",
+                    onchange: move |e: FormEvent| async move {
+                        if let Some(file) = e.files().into_iter().next() {
+                            load(file).await;
+                        }
+                    },
+                }
+            }
+        }
+    };
+
+    rsx! {
+        Button { variant: ButtonVariant::Outline, onclick: move |_| open.set(true), "Import" }
+        DialogRoot {
+            open: open(),
+            on_open_change: move |v| {
+                if !applying() {
+                    open.set(v);
+                }
+            },
+            DialogContent {
+                button {
+                    class: "dialog-close",
+                    r#type: "button",
+                    aria_label: "Close",
+                    tabindex: if open() { "0" } else { "-1" },
+                    disabled: applying(),
+                    onclick: move |_| open.set(false),
+                    "×"
+                }
+                DialogTitle { "Import Devices" }
+                DialogDescription { "Create and update devices and sensors from a CSV or JSON file." }
+                {body}
+            }
+        }
+    }
+}
@@ -20,11 +20,20 @@ pub use navbar::Navbar;
 mod sensor;
 pub use sensor::{DevicePage3, SensorPanel};
 
+mod snapshot;
+
+mod export;
+
+mod import;
+
 mod endpoints;
 pub use endpoints::{EndpointView, Storage, Storage2};
 
 mod projects;
 pub use projects::ProjectsView;
 
+mod settings;
+pub use settings::SettingsView;
+
 mod global;
 pub use global::Providers;
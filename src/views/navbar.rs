@@ -88,6 +88,11 @@ pub fn Navbar() -> Element {
                                     icon: fa_solid_icons::FaLink,
                                     "Endpoints"
                                 }
+                                SidebarLink {
+                                    to: Route::SettingsView {},
+                                    icon: fa_solid_icons::FaGear,
+                                    "Display"
+                                }
                             }
                         }
                     }
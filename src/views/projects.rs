@@ -40,13 +40,14 @@ impl<Lens> Store<AddProjectCtx, Lens> {
     }
 }
 
+/// State of a delete confirmation dialog, shared by the project, device and sensor pages.
 #[derive(Store)]
 pub struct DeleteCtx {
     pub is_open: bool,
     pub target: String,
 }
 
-#[store]
+#[store(pub)]
 impl<Lens> Store<DeleteCtx, Lens> {
     // This will automatically require `Writable` on the lens since it takes `&mut self`
     fn prompt_delete(&mut self, target: &str) {
@@ -5,25 +5,43 @@ use crate::components::button::{Button, ButtonVariant};
 use crate::components::card::{
     Card, CardAction, CardContent, CardDescription, CardFooter, CardHeader, CardTitle,
 };
+use crate::components::dialog::{DialogContent, DialogDescription, DialogRoot, DialogTitle};
 use crate::components::dropdown_menu::{
     DropdownMenu, DropdownMenuContent, DropdownMenuItem, DropdownMenuTrigger,
 };
 use crate::components::input::Input;
 use crate::components::label::Label;
+use crate::components::line_chart::{ChartPoint, LineChart};
+use crate::components::radio_group::{RadioGroup, RadioItem};
+use crate::components::switch::Switch;
 use crate::components::textarea::Textarea;
-use anyhow::{anyhow, Error, Result};
 use async_std::task::sleep;
-use base64::prelude::*;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDateTime, SecondsFormat, Utc};
 use dioxus::logger::tracing;
 use dioxus::prelude::*;
 use dioxus_free_icons::icons::fa_solid_icons;
 use dioxus_free_icons::Icon;
 use dioxus_primitives::toast::{use_toast, ToastOptions};
-use reqwest::Client;
-
+use futures::stream::{self, StreamExt};
+use reqwest::{Client, Url};
+
+use crate::alerts::{Severity, ThresholdRules};
+use crate::api::{use_api, ApiClient, ApiError, ApiResult};
+use crate::decode::{decode_sensor, DecodedValue};
+use crate::formula::Formula;
+use crate::snapshot_cache::{load_snapshot, use_snapshot_cache, SnapshotCache};
+use crate::timestamp::{use_now, TimeZoneChoice, Timestamp};
+use crate::units::DisplayFormat;
+
+use super::export::ExportDialog;
+use super::import::ImportDialog;
+use super::projects::{DeleteCtx, DeleteCtxStoreExt, DeleteCtxStoreImplExt};
+use super::snapshot::{SnapshotGallery, SnapshotUpload};
 use crate::models::{
-    ActiveDevice, ActiveInfo, ActiveNotify, Attribute, Device, EditDevice, EditSensor, Endpoint,
-    EndpointTrait, Endpoints, Project, Projects, RawData, Sensor, SensorType, SensorWithData,
+    record_status, status_key, ActiveDevice, ActiveInfo, ActiveNotify, ActiveNotifySetting, ActiveStatus, Attribute, Device,
+    EditActiveDevice, EditActiveNotify, EditDevice, EditRawData, EditSensor, Endpoint, Endpoints,
+    Preferences, Project, Projects, RawData, RawDataQuery, Sensor, SensorType, SensorWithData, StatusHistory,
+    StatusSegment, UptimeReport,
 };
 
 #[component]
@@ -227,21 +245,23 @@ pub fn DevicePage3(project_name: ReadSignal<String>) -> Element {
         }
     });
     // let project = projects().get(&project_name()).cloned();
-    let project_id = use_memo(move || project().map(|p| p.project_key));
-
-    let project_meta: Resource<Result<Vec<Device>>> = use_resource(move || async move {
-        let client = reqwest::Client::new();
-        let project_id = project_id().ok_or_else(|| anyhow!("No project id"))?;
-        let endpoint = endpoint().ok_or_else(|| anyhow!("No Endpoint"))?;
-        let url = endpoint.metadata();
-        let data = client
-            .get(url)
-            .header("CK", project_id.as_str())
-            .send()
-            .await?
-            .json::<Vec<Device>>()
-            .await?;
-        Ok(data)
+    let client = use_context::<Client>();
+    let api = use_memo(move || Some(ApiClient::new(client.clone(), &project()?, endpoint()?)));
+    use_context_provider(|| api);
+    // Snapshots are cached per project page, since ids are only unique within a project.
+    let mut snapshot_cache = use_context_provider(|| Signal::new(SnapshotCache::default()));
+    use_drop(move || {
+        if let Ok(mut cache) = snapshot_cache.try_write() {
+            cache.clear();
+        }
+    });
+
+    let mut metadata_problems = use_signal(Vec::<String>::new);
+    let mut project_meta: Resource<ApiResult<Vec<Device>>> = use_resource(move || async move {
+        let api = api().ok_or(ApiError::NotConfigured)?;
+        let metadata = api.metadata().await?;
+        metadata_problems.set(metadata.problems);
+        Ok(metadata.devices)
     });
 
     let device: Memo<Option<Device>> = use_memo(move || {
@@ -271,12 +291,21 @@ pub fn DevicePage3(project_name: ReadSignal<String>) -> Element {
         if let Some(resource) = &*project_meta.read() {
             match resource {
                 Ok(devices) => rsx! {
+                    if !metadata_problems.read().is_empty() {
+                        MetadataWarning { problems: metadata_problems() }
+                    }
                     match ctx.view_status()() {
                         ViewStatus::Device => rsx! {
-                            DevicesPanels3 { devices: devices.to_owned(), ctx }
+                            DevicesPanels3 {
+                                project_name,
+                                devices: devices.to_owned(),
+                                ctx,
+                                project_meta,
+                            }
                         },
                         ViewStatus::DeviceAttr => rsx! {
                             DeviceAttrPanel {
+                                project_name,
                                 project,
                                 endpoint,
                                 device,
@@ -286,10 +315,12 @@ pub fn DevicePage3(project_name: ReadSignal<String>) -> Element {
                         },
                         ViewStatus::Sensor => rsx! {
                             SensorsPanels3 {
+                                project_name,
                                 project,
                                 endpoint,
                                 device,
                                 ctx,
+                                project_meta,
                             }
                         },
                         ViewStatus::SensorAttr => rsx! {
@@ -304,8 +335,11 @@ pub fn DevicePage3(project_name: ReadSignal<String>) -> Element {
                         },
                     }
                 },
-                Err(_) => rsx! {
-                    p { "Load error" }
+                Err(error) => rsx! {
+                    ApiErrorCard {
+                        error: error.clone(),
+                        on_retry: move |_| project_meta.restart(),
+                    }
                 },
             }
         } else {
@@ -314,13 +348,520 @@ pub fn DevicePage3(project_name: ReadSignal<String>) -> Element {
     }
 }
 
+/// Lists the devices and sensors left out because the endpoint sent them in an unexpected shape.
+#[component]
+fn MetadataWarning(problems: Vec<String>) -> Element {
+    rsx! {
+        div { class: "border border-amber-500 rounded-lg p-4 mb-4",
+            details {
+                summary { class: "cursor-pointer text-amber-600 font-semibold",
+                    "{problems.len()} device or sensor entries could not be read and are hidden or incomplete"
+                }
+                ul { class: "list-disc pl-6 mt-2 text-sm",
+                    for problem in problems {
+                        li { {problem} }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Explains a failed request and lets the user send it again.
+#[component]
+pub fn ApiErrorCard(error: ApiError, on_retry: EventHandler<MouseEvent>) -> Element {
+    let detail = error.detail();
+    rsx! {
+        Card {
+            CardHeader {
+                CardTitle { {error.summary()} }
+                CardAction {
+                    Button {
+                        variant: ButtonVariant::Outline,
+                        onclick: move |e| on_retry.call(e),
+                        Icon { icon: fa_solid_icons::FaRotateRight }
+                        "Retry"
+                    }
+                }
+            }
+            if !detail.is_empty() {
+                CardContent {
+                    p { class: "text-sm break-all line-clamp-4", {detail} }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Store)]
+pub struct AddDeviceCtx {
+    pub is_open: bool,
+    pub id: String,
+    pub form: DeviceForm,
+    pub attributes: Vec<Attribute>,
+}
+
+#[store]
+impl<Lens> Store<AddDeviceCtx, Lens> {
+    fn open_dialog(&mut self) {
+        self.id().clear();
+        self.form().set(DeviceForm::default());
+        self.attributes().clear();
+        self.is_open().set(true);
+    }
+}
+
+/// Validates the "Add Device" dialog and builds the create request.
+fn new_edit_device(
+    id: &str,
+    form: &DeviceForm,
+    attributes: &[Attribute],
+    devices: &[Device],
+) -> Result<EditDevice, Vec<String>> {
+    let id = id.trim();
+    let mut errors = Vec::new();
+    if id.is_empty() {
+        errors.push("ID is required".to_string());
+    } else if id.contains(|c: char| c.is_whitespace() || c == '/') {
+        errors.push("ID must not contain spaces or '/'".to_string());
+    } else if devices.iter().any(|d| d.id == id) {
+        errors.push(format!("Device '{id}' already exists"));
+    }
+
+    let attributes: Vec<Attribute> = attributes
+        .iter()
+        .filter(|a| !a.key.trim().is_empty())
+        .cloned()
+        .collect();
+    let new_device = Device {
+        attributes: Some(attributes).filter(|a| !a.is_empty()),
+        ..Default::default()
+    };
+    match form.to_edit_device(&new_device) {
+        Ok(edit_device) if errors.is_empty() => Ok(EditDevice {
+            id: Some(id.to_string()),
+            ..edit_device
+        }),
+        Ok(_) => Err(errors),
+        Err(form_errors) => {
+            errors.extend(form_errors);
+            Err(errors)
+        }
+    }
+}
+
+/// Status requests kept in flight at once by the fleet overview.
+const FLEET_CONCURRENCY: usize = 16;
+
+/// Filters offered above the device cards.
+const FLEET_FILTERS: [(&str, &str); 4] = [
+    ("all", "All"),
+    ("problem", "Offline/Abnormal"),
+    ("online", "Online"),
+    ("unknown", "No status"),
+];
+
+type FleetStatus = HashMap<String, ApiResult<Option<ActiveInfo>>>;
+
+/// The status of a device in the fleet overview; a device without active setting is `Unset`.
+fn fleet_status(active: Option<&ApiResult<Option<ActiveInfo>>>) -> Option<ActiveStatus> {
+    match active? {
+        Ok(Some(info)) => Some(info.status),
+        Ok(None) => Some(ActiveStatus::Unset),
+        Err(_) => None,
+    }
+}
+
+fn fleet_filter_matches(filter: &str, status: Option<ActiveStatus>) -> bool {
+    match filter {
+        "problem" => status.is_some_and(|s| s.is_outage()),
+        "online" => status == Some(ActiveStatus::Online),
+        "unknown" => status.is_none_or(|s| s == ActiveStatus::Unset),
+        _ => true,
+    }
+}
+
 #[component]
-pub fn DevicesPanels3(devices: Vec<Device>, ctx: Store<PageContext>) -> Element {
+pub fn DevicesPanels3(
+    project_name: ReadSignal<String>,
+    devices: Vec<Device>,
+    ctx: Store<PageContext>,
+    project_meta: Resource<ApiResult<Vec<Device>>>,
+) -> Element {
+    let mut add_ctx = use_store(|| AddDeviceCtx {
+        is_open: false,
+        id: String::new(),
+        form: DeviceForm::default(),
+        attributes: Vec::new(),
+    });
+    let delete_ctx = use_store(|| DeleteCtx {
+        is_open: false,
+        target: String::new(),
+    });
+
+    let existing = devices.clone();
+    let add_errors = use_memo(use_reactive!(|existing| {
+        new_edit_device(
+            &add_ctx.id().cloned(),
+            &add_ctx.form().cloned(),
+            &add_ctx.attributes().cloned(),
+            &existing,
+        )
+        .err()
+    }));
+
+    let api = use_api();
+    let mut status_history = use_context::<Signal<StatusHistory>>();
+    let preferences = use_context::<Signal<Preferences>>();
+    let interval = use_memo(move || preferences().poll_interval(&project_name()));
+    let page_visible = use_page_visible();
+    let mut countdown = use_signal(|| interval() as i64);
+    let mut failures = use_signal(|| 0u32);
+    let device_ids: Vec<String> = devices.iter().map(|d| d.id.clone()).collect();
+    let mut fleet: Resource<FleetStatus> = use_resource(use_reactive!(|device_ids| async move {
+        let Some(api) = api() else {
+            return FleetStatus::new();
+        };
+        let fleet: FleetStatus = stream::iter(device_ids)
+            .map(|id| {
+                let api = api.clone();
+                async move {
+                    let active = api.active(&id).await;
+                    (id, active)
+                }
+            })
+            .buffer_unordered(FLEET_CONCURRENCY)
+            .collect()
+            .await;
+        // Back off only when no device answered, not for a single failing one.
+        if !fleet.is_empty() && fleet.values().all(|active| active.is_err()) {
+            *failures.write() += 1;
+        } else {
+            failures.set(0);
+        }
+        countdown.set(poll_delay(*interval.peek(), *failures.peek()) as i64);
+
+        let now = Utc::now().timestamp_millis();
+        let mut history = status_history.write();
+        for (id, active) in &fleet {
+            if let Ok(Some(info)) = active {
+                let key = status_key(&project_name(), id);
+                record_status(history.entry(key).or_default(), info.status, now);
+            }
+        }
+        fleet
+    }));
+    use_poll_countdown(fleet, interval, page_visible, countdown);
+    let mut filter = use_signal(|| "all".to_string());
+
+    let existing = devices.clone();
+    let on_add_submit = move |_| {
+        let existing = existing.clone();
+        async move {
+            let Some(api) = api() else {
+                return;
+            };
+            let toastapi = use_toast();
+            let edit_device = match new_edit_device(
+                &add_ctx.id().cloned(),
+                &add_ctx.form().cloned(),
+                &add_ctx.attributes().cloned(),
+                &existing,
+            ) {
+                Ok(edit_device) => edit_device,
+                Err(errors) => {
+                    toastapi.error(
+                        "Invalid device".to_string(),
+                        ToastOptions::new()
+                            .description(errors.join(", "))
+                            .duration(Duration::from_secs(10)),
+                    );
+                    return;
+                }
+            };
+
+            match api.create_device(&edit_device).await {
+                Ok(_) => {
+                    toastapi.success(
+                        format!("Add device '{}' success", edit_device.name),
+                        ToastOptions::new().duration(Duration::from_secs(5)),
+                    );
+                    add_ctx.is_open().set(false);
+                    project_meta.restart();
+                }
+                Err(e) => {
+                    toastapi.error(
+                        "Add device Failed".to_string(),
+                        ToastOptions::new()
+                            .description(format!("{e}"))
+                            .duration(Duration::from_secs(10)),
+                    );
+                }
+            }
+        }
+    };
+
+    let on_delete_confirm = move |_| async move {
+        let Some(api) = api() else {
+            return;
+        };
+        let target = delete_ctx.target().take();
+        delete_ctx.is_open().set(false);
+        let toastapi = use_toast();
+
+        match api.delete_device(&target).await {
+            Ok(_) => {
+                toastapi.success(
+                    format!("Delete device '{target}' success"),
+                    ToastOptions::new().duration(Duration::from_secs(5)),
+                );
+                project_meta.restart();
+            }
+            Err(e) => {
+                toastapi.error(
+                    "Delete device Failed".to_string(),
+                    ToastOptions::new()
+                        .description(format!("{e}"))
+                        .duration(Duration::from_secs(10)),
+                );
+            }
+        }
+    };
+
+    let new_dialog = rsx! {
+        DialogRoot {
+            open: *add_ctx.is_open().read(),
+            on_open_change: move |v| add_ctx.is_open().set(v),
+            DialogContent {
+                button {
+                    class: "dialog-close",
+                    r#type: "button",
+                    aria_label: "Close",
+                    tabindex: if *add_ctx.is_open().read() { "0" } else { "-1" },
+                    onclick: move |_| add_ctx.is_open().set(false),
+                    "×"
+                }
+                DialogTitle { "Add Device" }
+                DialogDescription {
+                    div { class: "flex flex-col gap-4",
+                        Label { html_for: "device_id", "ID" }
+                        Input {
+                            id: "device_id",
+                            value: add_ctx.id().cloned(),
+                            oninput: move |e: FormEvent| add_ctx.id().set(e.value()),
+                        }
+
+                        Label { html_for: "device_name", "Name" }
+                        Input {
+                            id: "device_name",
+                            value: add_ctx.form().read().name.clone(),
+                            oninput: move |e: FormEvent| add_ctx.form().write().name = e.value(),
+                        }
+
+                        Label { html_for: "device_type", "Type" }
+                        Input {
+                            id: "device_type",
+                            value: add_ctx.form().read().kind.clone(),
+                            oninput: move |e: FormEvent| add_ctx.form().write().kind = e.value(),
+                        }
+
+                        Label { html_for: "device_desc", "Desc" }
+                        Textarea {
+                            id: "device_desc",
+                            value: add_ctx.form().read().desc.clone(),
+                            oninput: move |e: FormEvent| add_ctx.form().write().desc = e.value(),
+                        }
+
+                        Label { html_for: "device_uri", "URI" }
+                        Input {
+                            id: "device_uri",
+                            value: add_ctx.form().read().uri.clone(),
+                            oninput: move |e: FormEvent| add_ctx.form().write().uri = e.value(),
+                        }
+
+                        div { class: "flex gap-4",
+                            Input {
+                                class: "input flex-1",
+                                r#type: "number",
+                                step: "any",
+                                placeholder: "Latitude",
+                                value: add_ctx.form().read().lat.clone(),
+                                oninput: move |e: FormEvent| add_ctx.form().write().lat = e.value(),
+                            }
+                            Input {
+                                class: "input flex-1",
+                                r#type: "number",
+                                step: "any",
+                                placeholder: "Longitude",
+                                value: add_ctx.form().read().lon.clone(),
+                                oninput: move |e: FormEvent| add_ctx.form().write().lon = e.value(),
+                            }
+                        }
+
+                        div { class: "flex items-center justify-between",
+                            Label { html_for: "device_attributes", "Attributes" }
+                            Button {
+                                variant: ButtonVariant::Ghost,
+                                onclick: move |_| {
+                                    add_ctx
+                                        .attributes()
+                                        .push(Attribute {
+                                            key: String::new(),
+                                            value: String::new(),
+                                        });
+                                },
+                                Icon { icon: fa_solid_icons::FaPlus }
+                            }
+                        }
+                        for (i , attr) in add_ctx.attributes().cloned().into_iter().enumerate() {
+                            div { class: "flex gap-2",
+                                Input {
+                                    class: "input flex-1",
+                                    placeholder: "Key",
+                                    value: attr.key,
+                                    oninput: move |e: FormEvent| add_ctx.attributes().write()[i].key = e.value(),
+                                }
+                                Input {
+                                    class: "input flex-1",
+                                    placeholder: "Value",
+                                    value: attr.value,
+                                    oninput: move |e: FormEvent| add_ctx.attributes().write()[i].value = e.value(),
+                                }
+                                Button {
+                                    variant: ButtonVariant::Ghost,
+                                    onclick: move |_| {
+                                        add_ctx.attributes().remove(i);
+                                    },
+                                    Icon { icon: fa_solid_icons::FaXmark }
+                                }
+                            }
+                        }
+
+                        if let Some(errors) = add_errors() {
+                            ul { class: "text-red-500 text-sm",
+                                for error in errors {
+                                    li { {error} }
+                                }
+                            }
+                        }
+
+                        Button {
+                            r#type: "submit",
+                            disabled: add_errors().is_some(),
+                            onclick: on_add_submit,
+                            "Submit"
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    let delete_dialog = rsx! {
+        DialogRoot {
+            open: *delete_ctx.is_open().read(),
+            on_open_change: move |v| delete_ctx.is_open().set(v),
+            DialogContent {
+                DialogTitle { "Delete Confirm" }
+                DialogDescription {
+                    div { class: "flex flex-col gap-4",
+                        "Delete device {delete_ctx.target()} and all of its sensors"
+                        div { class: "flex flex-row-reverse gap-4",
+                            Button {
+                                variant: ButtonVariant::Destructive,
+                                onclick: on_delete_confirm,
+                                "Yes"
+                            }
+                            Button {
+                                variant: ButtonVariant::Primary,
+                                onclick: move |_| delete_ctx.is_open().set(false),
+                                "NO"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    let fleet_status_of = |id: &str| fleet.read().as_ref().and_then(|f| f.get(id).cloned());
+    let mut counts: Vec<(ActiveStatus, usize)> = [
+        ActiveStatus::Online,
+        ActiveStatus::Offline,
+        ActiveStatus::Abnormal,
+        ActiveStatus::Start,
+        ActiveStatus::Stop,
+        ActiveStatus::Unset,
+    ]
+    .into_iter()
+    .map(|status| (status, 0))
+    .collect();
+    let mut unreachable = 0;
+    for d in &devices {
+        match fleet_status(fleet_status_of(&d.id).as_ref()) {
+            Some(status) => {
+                if let Some((_, count)) = counts.iter_mut().find(|(s, _)| *s == status) {
+                    *count += 1;
+                }
+            }
+            None if fleet.read().is_some() => unreachable += 1,
+            None => {}
+        }
+    }
+    let visible: Vec<(Device, Option<ApiResult<Option<ActiveInfo>>>)> = devices
+        .iter()
+        .map(|d| (d.clone(), fleet_status_of(&d.id)))
+        .filter(|(_, active)| fleet_filter_matches(&filter(), fleet_status(active.as_ref())))
+        .collect();
+
     rsx! {
-        h1 { class: "text-2xl mb-4", "Devices" }
+        div { class: "flex items-center justify-between mb-4",
+            h1 { class: "text-2xl", "Devices" }
+            div { class: "flex gap-2",
+                ImportDialog { project_meta }
+                Button { onclick: move |_| add_ctx.open_dialog(), "Add Device" }
+            }
+        }
+        {new_dialog}
+        {delete_dialog}
+        div { class: "flex flex-wrap items-center gap-4 mb-4",
+            for (status , count) in counts {
+                span { class: "flex items-center gap-1",
+                    span { class: "inline-block w-3 h-3 rounded {status_color(status)}" }
+                    "{status}: {count}"
+                }
+            }
+            if unreachable > 0 {
+                span { class: "text-red-500", "Status unavailable: {unreachable}" }
+            }
+            if fleet.read().is_none() {
+                span { "Loading status..." }
+            }
+            Button {
+                variant: ButtonVariant::Ghost,
+                onclick: move |_| fleet.restart(),
+                Icon { icon: fa_solid_icons::FaRotateRight }
+            }
+        }
+        div { class: "mb-4",
+            RadioGroup {
+                value: filter(),
+                horizontal: true,
+                on_value_change: move |v: String| filter.set(v),
+                for (i , (value , label)) in FLEET_FILTERS.into_iter().enumerate() {
+                    RadioItem { index: i, value, {label} }
+                }
+            }
+        }
         div { class: "grid sm:grid-cols-1 md:grid-cols-2 lg:grid-cols-3 xl:grid-cols-4 gap-4",
-            for d in devices {
-                DevicePanel3 { device: d.clone(), ctx }
+            for (d , active) in visible {
+                DevicePanel3 {
+                    key: "{d.id}",
+                    device: d,
+                    ctx,
+                    delete_ctx,
+                    active,
+                }
             }
         }
 
@@ -328,12 +869,24 @@ pub fn DevicesPanels3(devices: Vec<Device>, ctx: Store<PageContext>) -> Element
 }
 
 #[component]
-pub fn DevicePanel3(device: Device, ctx: Store<PageContext>) -> Element {
+pub fn DevicePanel3(
+    device: Device,
+    ctx: Store<PageContext>,
+    delete_ctx: Store<DeleteCtx>,
+    #[props(default)] active: Option<ApiResult<Option<ActiveInfo>>>,
+) -> Element {
+    let status = fleet_status(active.as_ref());
+    let last_data_time = match &active {
+        Some(Ok(Some(info))) => info.last_data_time,
+        _ => None,
+    };
     let device_clone = device.clone();
     let device_clone2 = device.clone();
+    let device_id = device.id.clone();
     let desc = device.desc.unwrap_or_default();
     let view_sensor = move |_| ctx.view_sensors(&device_clone.id);
     let view_device_attr = move |_| ctx.view_device_attr(&device_clone2.id);
+    let prompt_delete = move |_| delete_ctx.prompt_delete(&device_id);
     rsx! {
         div { class: " mb-2",
             Card {
@@ -341,6 +894,9 @@ pub fn DevicePanel3(device: Device, ctx: Store<PageContext>) -> Element {
                     CardTitle { {device.name} }
                     CardDescription { {desc} }
                     CardAction {
+                        Button { variant: ButtonVariant::Ghost, onclick: prompt_delete,
+                            Icon { icon: fa_solid_icons::FaTrash }
+                        }
                         Button {
                             variant: ButtonVariant::Ghost,
                             onclick: view_device_attr,
@@ -355,6 +911,23 @@ pub fn DevicePanel3(device: Device, ctx: Store<PageContext>) -> Element {
                 }
                 CardContent {
                     p { {device.id} }
+                    div { class: "flex items-center gap-2 text-sm mt-2",
+                        match status {
+                            Some(status) => rsx! {
+                                span { class: "px-2 rounded text-white {status_color(status)}", "{status}" }
+                            },
+                            None if active.is_some() => rsx! {
+                                span { class: "px-2 rounded border text-red-500", "Unknown" }
+                            },
+                            None => rsx! {},
+                        }
+                        if let Some(time) = last_data_time {
+                            span { class: "text-gray-500",
+                                "Last data: "
+                                RelativeTime { time }
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -410,22 +983,22 @@ pub fn DeviceHeader(
 
 #[component]
 pub fn SensorsPanels3(
+    project_name: ReadSignal<String>,
     project: Memo<Option<Project>>,
     endpoint: Memo<Option<Endpoint>>,
     device: Memo<Option<Device>>,
     ctx: Store<PageContext>,
+    project_meta: Resource<ApiResult<Vec<Device>>>,
 ) -> Element {
     let sensor_view = if project().is_some() && endpoint().is_some() && device().is_some() {
-        let project = project().unwrap();
-        let endpoint = endpoint().unwrap();
         let device = device().unwrap();
 
         rsx! {
             SensorView3 {
-                project,
-                endpoint,
+                project_name,
                 device,
                 ctx,
+                project_meta,
             }
         }
     } else {
@@ -443,27 +1016,234 @@ pub fn SensorsPanels3(
     }
 }
 
+#[derive(Store)]
+pub struct AddSensorCtx {
+    pub is_open: bool,
+    pub id: String,
+    pub form: SensorForm,
+    pub unit: String,
+}
+
+#[store]
+impl<Lens> Store<AddSensorCtx, Lens> {
+    fn open_dialog(&mut self) {
+        self.id().clear();
+        self.form().set(SensorForm::default());
+        self.unit().clear();
+        self.is_open().set(true);
+    }
+}
+
+/// Validates the "Add Sensor" dialog and builds the create request.
+fn new_edit_sensor(
+    id: &str,
+    form: &SensorForm,
+    unit: &str,
+    sensors: &[Sensor],
+) -> Result<EditSensor, Vec<String>> {
+    let id = id.trim();
+    let mut errors = Vec::new();
+    if id.is_empty() {
+        errors.push("ID is required".to_string());
+    } else if id.contains(|c: char| c.is_whitespace() || c == '/') {
+        errors.push("ID must not contain spaces or '/'".to_string());
+    } else if sensors.iter().any(|s| s.id == id) {
+        errors.push(format!("Sensor '{id}' already exists"));
+    }
+
+    let unit = unit.trim();
+    let new_sensor = Sensor {
+        attributes: (!unit.is_empty()).then(|| {
+            vec![Attribute {
+                key: "unit".to_string(),
+                value: unit.to_string(),
+            }]
+        }),
+        ..Default::default()
+    };
+    match form.to_edit_sensor(&new_sensor) {
+        Ok(edit_sensor) if errors.is_empty() => Ok(EditSensor {
+            id: Some(id.to_string()),
+            ..edit_sensor
+        }),
+        Ok(_) => Err(errors),
+        Err(form_errors) => {
+            errors.extend(form_errors);
+            Err(errors)
+        }
+    }
+}
+
+/// Number of polled samples kept per sensor for the in-session chart.
+const MAX_SAMPLES: usize = 500;
+
+/// Polling intervals offered in the sensor view, in seconds; 0 turns polling off.
+const POLL_INTERVALS: [(u64, &str); 6] = [
+    (0, "Off"),
+    (5, "5 s"),
+    (10, "10 s"),
+    (30, "30 s"),
+    (60, "1 min"),
+    (300, "5 min"),
+];
+
+/// Longest wait between polls after repeated errors.
+const MAX_BACKOFF_SECS: u64 = 300;
+
+/// Seconds until the next poll: the interval, doubled for each consecutive failure.
+fn poll_delay(interval: u64, failures: u32) -> u64 {
+    if failures == 0 {
+        return interval;
+    }
+    let backoff = interval.saturating_mul(1 << failures.min(10));
+    backoff.min(MAX_BACKOFF_SECS.max(interval))
+}
+
+/// Whether the page is visible, following the document's `visibilitychange` events.
+fn use_page_visible() -> ReadSignal<bool> {
+    let mut visible = use_signal(|| true);
+    // The handler is local to this eval, so several pollers don't replace each other's; any
+    // message tells the eval to remove it.
+    let eval = use_hook(|| {
+        document::eval(
+            r#"
+            const handler = () => dioxus.send(document.visibilityState === "visible");
+            document.addEventListener("visibilitychange", handler);
+            dioxus.send(document.visibilityState === "visible");
+            await dioxus.recv();
+            document.removeEventListener("visibilitychange", handler);
+            "#,
+        )
+    });
+    use_future(move || async move {
+        let mut eval = eval;
+        while let Ok(result) = eval.recv::<bool>().await {
+            visible.set(result);
+        }
+    });
+    use_drop(move || {
+        _ = eval.send(());
+    });
+    visible.into()
+}
+
+/// Counts `countdown` down once a second while polling is on and the page is visible, and
+/// restarts `resource` when it runs out. The resource sets the next countdown.
+fn use_poll_countdown<T: 'static>(
+    mut resource: Resource<T>,
+    interval: Memo<u64>,
+    visible: ReadSignal<bool>,
+    mut countdown: Signal<i64>,
+) {
+    use_future(move || async move {
+        let mut was_visible = true;
+        loop {
+            sleep(Duration::from_secs(1)).await;
+            let visible = visible();
+            // Catch up as soon as the page is shown again.
+            if visible && !was_visible {
+                countdown.set(0);
+            }
+            was_visible = visible;
+            if interval() == 0 || !visible || !resource.finished() {
+                continue;
+            }
+            *countdown.write() -= 1;
+            if countdown() <= 0 {
+                resource.restart();
+            }
+        }
+    });
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum PollState {
+    Live,
+    Paused,
+    Off,
+    Error,
+}
+
+/// Numeric samples of a gauge sensor after decoding, in the displayed unit, for charting.
+fn gauge_points(rows: &[RawData], sensor: &Sensor, format: &DisplayFormat) -> Vec<ChartPoint> {
+    rows.iter()
+        .filter_map(|row| {
+            let time = row.time?;
+            let value = decode_sensor(sensor, &row.value).ok()?.as_number()?;
+            Some(ChartPoint {
+                time: time.0,
+                value: format.convert(value),
+            })
+        })
+        .collect()
+}
+
+/// The sensor's display format in the user's unit system.
+fn use_display_format(sensor: ReadSignal<Sensor>) -> Memo<DisplayFormat> {
+    let preferences = use_context::<Signal<Preferences>>();
+    use_memo(move || {
+        let attributes = sensor().attributes.unwrap_or_default();
+        DisplayFormat::new(&attributes, preferences().unit_system)
+    })
+}
+
+#[component]
+fn GaugeChart(points: Vec<ChartPoint>, format: DisplayFormat) -> Element {
+    let preferences = use_context::<Signal<Preferences>>();
+    rsx! {
+        LineChart {
+            points,
+            time_zone: preferences().time_zone,
+            unit: format.unit.clone().unwrap_or_default(),
+            precision: format.precision,
+            min: format.min,
+            max: format.max,
+        }
+    }
+}
+
 #[component]
 pub fn SensorView3(
-    project: ReadSignal<Project>,
-    endpoint: ReadSignal<Endpoint>,
+    project_name: ReadSignal<String>,
     device: ReadSignal<Device>,
     ctx: Store<PageContext>,
+    project_meta: Resource<ApiResult<Vec<Device>>>,
 ) -> Element {
-    let mut timer = use_signal(|| 10);
-    let mut resource: Resource<Result<_, Error>> = use_resource(move || async move {
+    let api = use_api();
+    let mut preferences = use_context::<Signal<Preferences>>();
+    let interval = use_memo(move || preferences().poll_interval(&project_name()));
+    let visible = use_page_visible();
+    let mut countdown = use_signal(|| interval() as i64);
+    let mut failures = use_signal(|| 0u32);
+    let mut samples = use_signal(HashMap::<String, Vec<RawData>>::new);
+    let mut resource: Resource<ApiResult<_>> = use_resource(move || async move {
+        let api = api().ok_or(ApiError::NotConfigured)?;
         let device_id = device().id;
-        let project_id = project().project_key;
-        let client = reqwest::Client::new();
 
         let sensors = device().sensors.unwrap_or_default();
-        let raw_datas = client
-            .get(endpoint().rawdata(&device_id))
-            .header("CK", project_id)
-            .send()
-            .await?
-            .json::<Vec<RawData>>()
-            .await?;
+        let raw_datas = api.rawdata(&device_id).await;
+        match raw_datas {
+            Ok(_) => failures.set(0),
+            Err(_) => *failures.write() += 1,
+        }
+        countdown.set(poll_delay(*interval.peek(), *failures.peek()) as i64);
+        let raw_datas = raw_datas?;
+        {
+            let mut samples = samples.write();
+            for data in &raw_datas {
+                let history = samples.entry(data.id.clone()).or_default();
+                // Samples without a time can't be told apart, so only timed ones are deduplicated.
+                let seen = history
+                    .last()
+                    .is_some_and(|last| last.time.is_some() && last.time == data.time);
+                if !seen {
+                    history.push(data.clone());
+                    if history.len() > MAX_SAMPLES {
+                        history.remove(0);
+                    }
+                }
+            }
+        }
         let raw_data_map: HashMap<String, RawData> =
             raw_datas.into_iter().map(|d| (d.id.clone(), d)).collect();
         let sensor_data: Vec<_> = sensors
@@ -477,100 +1257,568 @@ pub fn SensorView3(
         Ok(sensor_data)
     });
 
-    use_future(move || async move {
-        loop {
-            sleep(Duration::from_secs(1)).await;
-            *timer.write() -= 1;
-            if timer() < 0 {
-                timer.set(10);
-                if resource.finished() {
-                    resource.restart();
-                }
-            }
-        }
-    });
+    use_poll_countdown(resource, interval, visible, countdown);
 
-    rsx! {
-        Button {
-            onclick: move |_| {
-                resource.restart();
-                timer.set(10);
-            },
-            "Refresh: {timer()}"
+    let poll_state = use_memo(move || {
+        if interval() == 0 {
+            PollState::Off
+        } else if failures() > 0 {
+            PollState::Error
+        } else if !visible() {
+            PollState::Paused
+        } else {
+            PollState::Live
         }
-        div { class: "grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 xl:grid-cols-4 gap-4 p-4",
-            if let Some(response) = &*resource.read() {
-                match response {
-                    Ok(sensors) => rsx! {
-                        for s in sensors {
-                            SensorPanel3 {
-                                project,
-                                endpoint,
-                                device,
-                                ctx,
-                                sensor_data: s.clone(),
-                            }
-                        }
-                    },
-                    Err(err) => rsx! { "Failed to fetch response: {err}" },
-                }
-            } else {
-                "Loading..."
-            }
+    });
+    let poll_label = use_memo(move || match poll_state() {
+        PollState::Live => format!("Live · next in {}s", countdown().max(0)),
+        PollState::Paused => "Paused · page hidden".to_string(),
+        PollState::Off => "Polling off".to_string(),
+        PollState::Error => format!(
+            "Error · {} failed, retry in {}s",
+            failures(),
+            countdown().max(0)
+        ),
+    });
+    let poll_color = match poll_state() {
+        PollState::Live => "bg-green-500",
+        PollState::Paused => "bg-amber-500",
+        PollState::Off => "bg-gray-400",
+        PollState::Error => "bg-red-500",
+    };
+    let set_interval = move |value: String| {
+        if let Ok(secs) = value.parse::<u64>() {
+            preferences
+                .write()
+                .poll_intervals
+                .insert(project_name(), secs);
+            countdown.set(poll_delay(secs, failures()) as i64);
         }
-    }
-}
+    };
+
+    let mut add_ctx = use_store(|| AddSensorCtx {
+        is_open: false,
+        id: String::new(),
+        form: SensorForm::default(),
+        unit: String::new(),
+    });
+    let delete_ctx = use_store(|| DeleteCtx {
+        is_open: false,
+        target: String::new(),
+    });
+
+    // Values older than the monitor period are stale; without a monitor, the user's setting applies.
+    let monitor_period: Resource<Option<u64>> = use_resource(move || async move {
+        let api = api()?;
+        let setting = api.active_setting(&device().id).await.ok()?;
+        setting.enable.then(|| parse_period(&setting.period).ok()).flatten()
+    });
+    let stale_after = use_memo(move || {
+        monitor_period
+            .read()
+            .flatten()
+            .unwrap_or(preferences().stale_after_secs)
+    });
+
+    let add_errors = use_memo(move || {
+        new_edit_sensor(
+            &add_ctx.id().cloned(),
+            &add_ctx.form().cloned(),
+            &add_ctx.unit().cloned(),
+            &device().sensors.unwrap_or_default(),
+        )
+        .err()
+    });
+
+    let on_add_submit = move |_| async move {
+        let Some(api) = api() else {
+            return;
+        };
+        let toastapi = use_toast();
+        let edit_sensor = match new_edit_sensor(
+            &add_ctx.id().cloned(),
+            &add_ctx.form().cloned(),
+            &add_ctx.unit().cloned(),
+            &device().sensors.unwrap_or_default(),
+        ) {
+            Ok(edit_sensor) => edit_sensor,
+            Err(errors) => {
+                toastapi.error(
+                    "Invalid sensor".to_string(),
+                    ToastOptions::new()
+                        .description(errors.join(", "))
+                        .duration(Duration::from_secs(10)),
+                );
+                return;
+            }
+        };
+
+        match api.create_sensor(&device().id, &edit_sensor).await {
+            Ok(_) => {
+                toastapi.success(
+                    format!("Add sensor '{}' success", edit_sensor.name),
+                    ToastOptions::new().duration(Duration::from_secs(5)),
+                );
+                add_ctx.is_open().set(false);
+                project_meta.restart();
+            }
+            Err(e) => {
+                toastapi.error(
+                    "Add sensor Failed".to_string(),
+                    ToastOptions::new()
+                        .description(format!("{e}"))
+                        .duration(Duration::from_secs(10)),
+                );
+            }
+        }
+    };
+
+    let on_delete_confirm = move |_| async move {
+        let Some(api) = api() else {
+            return;
+        };
+        let target = delete_ctx.target().take();
+        delete_ctx.is_open().set(false);
+        let toastapi = use_toast();
+
+        match api.delete_sensor(&device().id, &target).await {
+            Ok(_) => {
+                toastapi.success(
+                    format!("Delete sensor '{target}' success"),
+                    ToastOptions::new().duration(Duration::from_secs(5)),
+                );
+                samples.write().remove(&target);
+                project_meta.restart();
+            }
+            Err(e) => {
+                toastapi.error(
+                    "Delete sensor Failed".to_string(),
+                    ToastOptions::new()
+                        .description(format!("{e}"))
+                        .duration(Duration::from_secs(10)),
+                );
+            }
+        }
+    };
+
+    let new_dialog = rsx! {
+        DialogRoot {
+            open: *add_ctx.is_open().read(),
+            on_open_change: move |v| add_ctx.is_open().set(v),
+            DialogContent {
+                button {
+                    class: "dialog-close",
+                    r#type: "button",
+                    aria_label: "Close",
+                    tabindex: if *add_ctx.is_open().read() { "0" } else { "-1" },
+                    onclick: move |_| add_ctx.is_open().set(false),
+                    "×"
+                }
+                DialogTitle { "Add Sensor" }
+                DialogDescription {
+                    div { class: "flex flex-col gap-4",
+                        Label { html_for: "sensor_id", "ID" }
+                        Input {
+                            id: "sensor_id",
+                            value: add_ctx.id().cloned(),
+                            oninput: move |e: FormEvent| add_ctx.id().set(e.value()),
+                        }
+
+                        Label { html_for: "sensor_name", "Name" }
+                        Input {
+                            id: "sensor_name",
+                            value: add_ctx.form().read().name.clone(),
+                            oninput: move |e: FormEvent| add_ctx.form().write().name = e.value(),
+                        }
+
+                        Label { html_for: "sensor_type", "Type" }
+                        RadioGroup {
+                            id: "sensor_type",
+                            value: add_ctx.form().read().kind.as_str().to_string(),
+                            horizontal: true,
+                            on_value_change: move |v: String| {
+                                if let Some(kind) = SensorType::from_api_name(&v) {
+                                    add_ctx.form().write().kind = kind;
+                                }
+                            },
+                            for (i , kind) in SensorType::ALL.into_iter().enumerate() {
+                                RadioItem { index: i, value: kind.as_str().to_string(), "{kind}" }
+                            }
+                        }
+
+                        Label { html_for: "sensor_unit", "Unit" }
+                        Input {
+                            id: "sensor_unit",
+                            placeholder: "°C",
+                            value: add_ctx.unit().cloned(),
+                            oninput: move |e: FormEvent| add_ctx.unit().set(e.value()),
+                        }
+
+                        Label { html_for: "sensor_formula", "Formula" }
+                        Input {
+                            id: "sensor_formula",
+                            placeholder: "value[0] * 0.1",
+                            value: add_ctx.form().read().formula.clone(),
+                            oninput: move |e: FormEvent| add_ctx.form().write().formula = e.value(),
+                        }
+
+                        if let Some(errors) = add_errors() {
+                            ul { class: "text-red-500 text-sm",
+                                for error in errors {
+                                    li { {error} }
+                                }
+                            }
+                        }
+
+                        Button {
+                            r#type: "submit",
+                            disabled: add_errors().is_some(),
+                            onclick: on_add_submit,
+                            "Submit"
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    let delete_dialog = rsx! {
+        DialogRoot {
+            open: *delete_ctx.is_open().read(),
+            on_open_change: move |v| delete_ctx.is_open().set(v),
+            DialogContent {
+                DialogTitle { "Delete Confirm" }
+                DialogDescription {
+                    div { class: "flex flex-col gap-4",
+                        "Delete sensor {delete_ctx.target()} of {device().name}"
+                        div { class: "flex flex-row-reverse gap-4",
+                            Button {
+                                variant: ButtonVariant::Destructive,
+                                onclick: on_delete_confirm,
+                                "Yes"
+                            }
+                            Button {
+                                variant: ButtonVariant::Primary,
+                                onclick: move |_| delete_ctx.is_open().set(false),
+                                "NO"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "flex flex-wrap items-center gap-4",
+            Button { onclick: move |_| resource.restart(), "Refresh" }
+            RadioGroup {
+                value: interval().to_string(),
+                horizontal: true,
+                on_value_change: set_interval,
+                aria_label: "Polling interval",
+                for (i , (secs , label)) in POLL_INTERVALS.into_iter().enumerate() {
+                    RadioItem { index: i, value: secs.to_string(), {label} }
+                }
+            }
+            span { class: "flex items-center gap-2 text-sm",
+                span { class: "inline-block w-2 h-2 rounded-full {poll_color}" }
+                "{poll_label}"
+            }
+            Button {
+                variant: ButtonVariant::Outline,
+                onclick: move |_| add_ctx.open_dialog(),
+                "Add Sensor"
+            }
+            ExportDialog {
+                device,
+                sensors: device().sensors.unwrap_or_default(),
+                selected: device().sensors.unwrap_or_default().into_iter().map(|s| s.id).collect::<Vec<_>>(),
+            }
+        }
+        {new_dialog}
+        {delete_dialog}
+        div { class: "grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 xl:grid-cols-4 gap-4 p-4",
+            if let Some(response) = &*resource.read() {
+                match response {
+                    Ok(sensors) => rsx! {
+                        for s in sensors {
+                            SensorPanel3 {
+                                key: "{s.sensor.id}",
+                                device,
+                                ctx,
+                                delete_ctx,
+                                sensor_data: s.clone(),
+                                samples: samples.read().get(&s.sensor.id).cloned().unwrap_or_default(),
+                                stale_after: stale_after(),
+                                on_refresh: move |_| resource.restart(),
+                            }
+                        }
+                    },
+                    Err(error) => rsx! {
+                        ApiErrorCard {
+                            error: error.clone(),
+                            on_retry: move |_| resource.restart(),
+                        }
+                    },
+                }
+            } else {
+                "Loading..."
+            }
+        }
+    }
+}
+
+/// The sensor's displayed value, or why its formula could not be applied.
+fn display_value(sensor: &Sensor, format: &DisplayFormat, values: &[String]) -> String {
+    match decode_sensor(sensor, values) {
+        Ok(value) => format.format(&value),
+        Err(e) => format!("{} ({e})", values.join(" ")),
+    }
+}
+
+/// A time in the user's time zone and format.
+#[component]
+fn TimeLabel(time: Option<Timestamp>) -> Element {
+    let preferences = use_context::<Signal<Preferences>>();
+    match time {
+        Some(time) => rsx! {
+            span { {preferences().format_time(&time)} }
+        },
+        None => rsx! {
+            span { "-" }
+        },
+    }
+}
+
+/// How long ago a time was, e.g. `12 s ago`, with the full time on hover.
+#[component]
+fn RelativeTime(time: Timestamp) -> Element {
+    let preferences = use_context::<Signal<Preferences>>();
+    let now = use_now();
+    rsx! {
+        span { title: preferences().format_time(&time), {time.relative(now())} }
+    }
+}
+
+/// Outline of a sensor card for the severity of its value.
+fn severity_outline(severity: Option<Severity>) -> &'static str {
+    match severity {
+        Some(Severity::Warning) => "outline-2 outline-amber-500",
+        Some(Severity::Critical) => "outline-2 outline-red-500",
+        _ => "",
+    }
+}
+
+fn severity_text(severity: Option<Severity>) -> &'static str {
+    match severity {
+        Some(Severity::Warning) => "text-amber-600",
+        Some(Severity::Critical) => "text-red-600",
+        _ => "",
+    }
+}
+
+/// Tells the user that a sensor's value crossed a threshold, by toast and, if enabled, by a
+/// browser notification.
+fn announce_severity(name: &str, severity: Severity, value: &str, notify: bool) {
+    let toastapi = use_toast();
+    let title = match severity {
+        Severity::Normal => format!("{name} is back to normal"),
+        severity => format!("{severity}: {name}"),
+    };
+    let options = ToastOptions::new()
+        .description(value.to_string())
+        .duration(Duration::from_secs(10));
+    match severity {
+        Severity::Normal => toastapi.info(title.clone(), options),
+        Severity::Warning => toastapi.warning(title.clone(), options),
+        Severity::Critical => toastapi.error(title.clone(), options),
+    }
+
+    if notify {
+        let title = serde_json::to_string(&title).unwrap_or_default();
+        let body = serde_json::to_string(value).unwrap_or_default();
+        _ = document::eval(&format!(
+            r#"
+            if ("Notification" in window && Notification.permission === "granted") {{
+                new Notification({title}, {{ body: {body} }});
+            }}
+            "#
+        ));
+    }
+}
+
+#[component]
+fn SensorValue(
+    value: Option<Result<DecodedValue, String>>,
+    format: DisplayFormat,
+    #[props(default)] severity: Option<Severity>,
+) -> Element {
+    match value {
+        Some(Ok(value)) => rsx! {
+            p { class: "text-2xl font-bold truncate {severity_text(severity)}",
+                {format.format(&value)}
+            }
+        },
+        Some(Err(error)) => rsx! {
+            p { class: "text-sm text-red-500", "{error}" }
+        },
+        None => rsx! {
+            p { class: "text-2xl font-bold", "-" }
+        },
+    }
+}
 
 #[component]
 pub fn SensorPanel3(
-    project: ReadSignal<Project>,
-    endpoint: ReadSignal<Endpoint>,
     device: ReadSignal<Device>,
     ctx: Store<PageContext>,
+    delete_ctx: Store<DeleteCtx>,
     sensor_data: ReadSignal<SensorWithData>,
+    samples: ReadSignal<Vec<RawData>>,
+    /// Age in seconds after which the latest value is flagged as stale.
+    stale_after: ReadSignal<u64>,
+    /// Reloads the sensors of the device, e.g. after a snapshot upload.
+    on_refresh: EventHandler<()>,
 ) -> Element {
     let data = use_memo(move || sensor_data().data);
-    let value = use_memo(move || data().map(|d| d.value.join(" ")).unwrap_or_default());
-
     let sensor = use_memo(move || sensor_data().sensor);
+    let value = use_memo(move || data().map(|d| decode_sensor(&sensor(), &d.value)));
+    let format = use_display_format(sensor.into());
+    let now = use_now();
+    let stale = use_memo(move || {
+        let age = data().and_then(|d| d.time).map(|t| (now() - t.0).num_seconds());
+        age.is_some_and(|age| u64::try_from(age).is_ok_and(|age| age > stale_after()))
+    });
 
-    let img_data: Resource<Result<_, Error>> = use_resource(move || async move {
-        let sensor = sensor_data().sensor;
-        let data = sensor_data().data;
-        let first_value = data
-            .map(|d| d.value.first().cloned().unwrap_or_default())
-            .unwrap_or_default();
-        if sensor.kind == SensorType::Snapshot && first_value.len() > 11 {
-            let client = reqwest::Client::new();
-            let sensor_id = sensor.id;
-            let device_id = device().id;
-            let project_key = project().project_key;
-            let snapshot_id = first_value[11..].to_string();
-            let url = endpoint().snapshot(&device_id, &sensor_id, &snapshot_id);
-            let img = client
-                .get(url)
-                .header("CK", project_key.as_str())
-                .send()
-                .await?
-                .bytes()
-                .await?;
-            let img_b64 = String::from("data:image/jpeg;base64,") + &BASE64_STANDARD.encode(img);
-            Ok(img_b64)
-        } else {
-            Err(anyhow!("Not a snapshot"))
+    let rules = use_memo(move || ThresholdRules::new(&sensor().attributes.unwrap_or_default()));
+    let severity = use_memo(move || match value() {
+        Some(Ok(value)) if !rules().is_empty() => Some(rules().severity(&value)),
+        _ => None,
+    });
+    // Severity of the previous value, so only a change raises an alert and not the first load.
+    let mut last_severity = use_signal(|| None::<Severity>);
+    let preferences = use_context::<Signal<Preferences>>();
+    use_effect(move || {
+        let current = severity();
+        let previous = *last_severity.peek();
+        last_severity.set(current);
+        if let (Some(previous), Some(current)) = (previous, current) {
+            if previous != current {
+                let value = match value() {
+                    Some(Ok(value)) => format().format(&value),
+                    _ => String::new(),
+                };
+                let name = format().label.unwrap_or_else(|| sensor().name);
+                announce_severity(&name, current, &value, preferences().browser_notifications);
+            }
+        }
+    });
+
+    let api = use_api();
+    let mut snapshot_cache = use_snapshot_cache();
+    let snapshot_id = use_memo(move || match value() {
+        Some(Ok(value)) => value.snapshot_id().map(str::to_string),
+        _ => None,
+    });
+    // The URL this card holds in the snapshot cache.
+    let mut shown_url = use_signal(|| None::<String>);
+    // `None` when the value is not a snapshot.
+    let img_data: Resource<Option<ApiResult<String>>> = use_resource(move || async move {
+        let snapshot_id = snapshot_id()?;
+        let Some(api) = api() else {
+            return Some(Err(ApiError::NotConfigured));
+        };
+        let sensor_id = sensor().id;
+        let device_id = device().id;
+        let snapshot =
+            load_snapshot(&api, snapshot_cache, &device_id, &sensor_id, &snapshot_id).await;
+        Some(snapshot.map(|snapshot| {
+            if let Some(old) = shown_url.replace(Some(snapshot.url.clone())) {
+                snapshot_cache.write().release(&old);
+            }
+            snapshot.url
+        }))
+    });
+    use_drop(move || {
+        if let (Ok(mut url), Ok(mut cache)) = (shown_url.try_write(), snapshot_cache.try_write()) {
+            if let Some(url) = url.take() {
+                cache.release(&url);
+            }
         }
     });
 
     let sensor_id = use_memo(move || sensor().id.clone());
+    let points = use_memo(move || {
+        if sensor().kind == SensorType::Gauge {
+            gauge_points(&samples(), &sensor(), &format())
+        } else {
+            Vec::new()
+        }
+    });
+
+    // Optimistic switch state, kept until the next poll brings a new value or the write fails.
+    let mut pending_switch = use_signal(|| None::<bool>);
+    let mut switch_in_flight = use_signal(|| false);
+    let switch_on = use_memo(move || {
+        pending_switch()
+            .unwrap_or_else(|| value().and_then(|v| v.ok()?.as_bool()).unwrap_or(false))
+    });
+    use_effect(move || {
+        sensor_data();
+        if !*switch_in_flight.peek() {
+            pending_switch.set(None);
+        }
+    });
+
+    let toggle_switch = move |on: bool| async move {
+        let Some(api) = api() else {
+            return;
+        };
+        let toastapi = use_toast();
+        let edit = EditRawData {
+            id: sensor().id,
+            value: DecodedValue::Bool(on).encode(),
+            time: None,
+        };
+
+        pending_switch.set(Some(on));
+        switch_in_flight.set(true);
+        let result = api.write_rawdata(&device().id, &[edit]).await;
+        switch_in_flight.set(false);
+
+        match result {
+            Ok(_) => {
+                let state = if on { "on" } else { "off" };
+                toastapi.success(
+                    format!("{} switched {state}", sensor().name),
+                    ToastOptions::new().duration(Duration::from_secs(5)),
+                );
+            }
+            Err(e) => {
+                pending_switch.set(None);
+                toastapi.error(
+                    "Switch Failed".to_string(),
+                    ToastOptions::new()
+                        .description(format!("{e}"))
+                        .duration(Duration::from_secs(10)),
+                );
+            }
+        }
+    };
 
     let btnclick = move |_| ctx.view_sensor_attr(&sensor_id());
+    let prompt_delete = move |_| delete_ctx.prompt_delete(&sensor_id());
 
     rsx! {
-        Card {
+        Card { class: severity_outline(severity()),
             CardHeader {
                 // CardTitle displays the main heading.
-                CardTitle { {sensor().name} }
+                CardTitle { {format().label.unwrap_or_else(|| sensor().name)} }
                 CardAction {
+                    Button { variant: ButtonVariant::Ghost, onclick: prompt_delete,
+                        Icon { icon: fa_solid_icons::FaTrash }
+                    }
                     Button { variant: ButtonVariant::Ghost, onclick: btnclick,
                         Icon { icon: fa_solid_icons::FaSliders }
                     }
@@ -579,25 +1827,73 @@ pub fn SensorPanel3(
             // CardContent holds the main body content.
             CardContent {
                 div { class: "max-h-32 h-32 flex justify-center items-center",
-                    if let Some(image_data) = &*img_data.read() {
+                    if sensor().kind == SensorType::Switch {
+                        div { class: "flex items-center gap-4",
+                            Switch {
+                                checked: Some(switch_on()),
+                                disabled: switch_in_flight(),
+                                on_checked_change: toggle_switch,
+                                aria_label: "Toggle {sensor().name}",
+                            }
+                            p { class: "text-2xl font-bold",
+                                if switch_on() {
+                                    "On"
+                                } else {
+                                    "Off"
+                                }
+                            }
+                        }
+                        if let Some(Err(error)) = value() {
+                            p { class: "text-sm text-red-500", "{error}" }
+                        }
+                    } else if let Some(Some(image_data)) = &*img_data.read() {
                         match image_data {
                             Ok(image_data) => rsx! {
                                 img { class: "h-32 object-contain", src: image_data.as_str() }
                             },
                             Err(_) => rsx! {
-                                p { class: "text-2xl font-bold truncate", "{value}" }
+                                SensorValue { value: value(), format: format(), severity: severity() }
 
                             },
                         }
                     } else {
-                        p { class: "text-2xl font-bold truncate", "{value}" }
+                        SensorValue { value: value(), format: format(), severity: severity() }
+                    }
+                }
+                if points().len() >= 2 {
+                    GaugeChart { points: points(), format: format() }
+                }
+                if sensor().kind == SensorType::Snapshot {
+                    SnapshotUpload {
+                        device_id: device().id,
+                        sensor_id: sensor_id(),
+                        on_uploaded: on_refresh,
                     }
                 }
             }
             // CardFooter contains footer actions or information.
             CardFooter {
                 div { class: "flex flex-col",
-                    p { {data().map(|d| d.time).unwrap_or_default()} }
+                    p { class: "flex items-center gap-2",
+                        match data().map(|d| d.time) {
+                            Some(Some(time)) => rsx! {
+                                RelativeTime { time }
+                            },
+                            Some(None) => rsx! {
+                                span { "Unknown time" }
+                            },
+                            None => rsx! {
+                                span { "No data" }
+                            },
+                        }
+                        if stale() {
+                            span {
+                                class: "px-2 rounded text-white bg-gray-500",
+                                title: "No new value for more than {format_duration_ms(i64::try_from(stale_after()).unwrap_or(i64::MAX).saturating_mul(1000))}",
+                                "Stale"
+                            }
+                        }
+                    }
                     p { {sensor().id} }
                 }
             }
@@ -607,21 +1903,19 @@ pub fn SensorPanel3(
 
 #[component]
 pub fn DeviceAttrPanel(
+    project_name: ReadSignal<String>,
     project: Memo<Option<Project>>,
     endpoint: Memo<Option<Endpoint>>,
     device: Memo<Option<Device>>,
     ctx: Store<PageContext>,
-    project_meta: Resource<Result<Vec<Device>>>,
+    project_meta: Resource<ApiResult<Vec<Device>>>,
 ) -> Element {
     let panel = if project().is_some() && endpoint().is_some() && device().is_some() {
-        let project = project().unwrap();
-        let endpoint = endpoint().unwrap();
         let device = device().unwrap();
 
         rsx! {
             DeviceAttrPanelImpl {
-                project,
-                endpoint,
+                project_name,
                 device,
                 ctx,
                 project_meta,
@@ -644,46 +1938,151 @@ pub fn DeviceAttrPanel(
     }
 }
 
+/// Editable text of the "Device Info" form.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DeviceForm {
+    name: String,
+    kind: String,
+    desc: String,
+    uri: String,
+    lat: String,
+    lon: String,
+}
+
+impl DeviceForm {
+    fn from_device(device: &Device) -> Self {
+        DeviceForm {
+            name: device.name.clone(),
+            kind: device.kind.clone(),
+            desc: device.desc.clone().unwrap_or_default(),
+            uri: device.uri.clone().unwrap_or_default(),
+            lat: device.lat.map(|v| v.to_string()).unwrap_or_default(),
+            lon: device.lon.map(|v| v.to_string()).unwrap_or_default(),
+        }
+    }
+
+    /// Validates the form and builds the update request, keeping the device's attributes.
+    fn to_edit_device(&self, device: &Device) -> Result<EditDevice, Vec<String>> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push("Name is required".to_string());
+        }
+        if self.kind.trim().is_empty() {
+            errors.push("Type is required".to_string());
+        }
+        let lat = parse_coordinate(&self.lat, "Latitude", 90.0).unwrap_or_else(|e| {
+            errors.push(e);
+            None
+        });
+        let lon = parse_coordinate(&self.lon, "Longitude", 180.0).unwrap_or_else(|e| {
+            errors.push(e);
+            None
+        });
+        if let Err(e) = check_uri(&device.uri, &self.uri) {
+            errors.push(e);
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(EditDevice {
+            id: None,
+            name: self.name.trim().to_string(),
+            kind: self.kind.trim().to_string(),
+            desc: edited_optional(&device.desc, &self.desc),
+            uri: edited_optional(&device.uri, &self.uri),
+            lat,
+            lon,
+            attributes: device.attributes.clone(),
+        })
+    }
+}
+
+/// Parses an optional coordinate and checks it lies within `-limit..=limit`.
+fn parse_coordinate(text: &str, name: &str, limit: f64) -> Result<Option<f64>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    match text.parse::<f64>() {
+        Ok(v) if v.is_finite() && v.abs() <= limit => Ok(Some(v)),
+        Ok(_) => Err(format!("{name} must be between -{limit} and {limit}")),
+        Err(_) => Err(format!("{name} must be a number")),
+    }
+}
+
 #[component]
 pub fn DeviceAttrPanelImpl(
-    project: ReadSignal<Project>,
-    endpoint: ReadSignal<Endpoint>,
+    project_name: ReadSignal<String>,
     device: ReadSignal<Device>,
     ctx: Store<PageContext>,
-    project_meta: Resource<Result<Vec<Device>>>,
+    project_meta: Resource<ApiResult<Vec<Device>>>,
 ) -> Element {
     let mut attributes = use_signal(|| device().attributes.unwrap_or_default().clone());
     let is_dirty = use_memo(move || attributes() != device().attributes.unwrap_or_default());
 
-    let mut device_info = use_signal(|| device().clone());
-    let is_device_dirty = use_memo(move || device_info() != device());
+    let mut device_info = use_signal(|| DeviceForm::from_device(&device()));
+    let is_device_dirty = use_memo(move || device_info() != DeviceForm::from_device(&device()));
+    let device_errors = use_memo(move || device_info().to_edit_device(&device()).err());
+
+    let api = use_api();
+    let save_device = move |_| async move {
+        let Some(api) = api() else {
+            return;
+        };
+        let toastapi = use_toast();
+        let edit_device = match device_info().to_edit_device(&device()) {
+            Ok(edit_device) => edit_device,
+            Err(errors) => {
+                toastapi.error(
+                    "Invalid device info".to_string(),
+                    ToastOptions::new()
+                        .description(errors.join(", "))
+                        .duration(Duration::from_secs(10)),
+                );
+                return;
+            }
+        };
+
+        let result = api.update_device(&device().id, &edit_device).await;
+
+        match result {
+            Ok(text) => {
+                toastapi.success(
+                    "Updated".to_string(),
+                    ToastOptions::new()
+                        .description(text)
+                        .duration(Duration::from_secs(5)),
+                );
+                project_meta.restart();
+            }
+            Err(e) => {
+                toastapi.error(
+                    "Update Failed".to_string(),
+                    ToastOptions::new()
+                        .description(format!("{e}"))
+                        .duration(Duration::from_secs(10)),
+                );
+            }
+        }
+    };
 
     let save_attrs = move |_| async move {
+        let Some(api) = api() else {
+            return;
+        };
         let edit_device = EditDevice {
-            name: device().name.clone(),
-            kind: device().kind.clone(),
             attributes: Some(attributes().clone()),
-            ..Default::default()
+            ..EditDevice::from(&device())
         };
-        let client = Client::new();
-        let url = endpoint().device(&device().id);
         let toastapi = use_toast();
 
         let json_text = serde_json::to_string(&edit_device);
         tracing::debug!("{:?}", json_text);
-        let result = client
-            .put(url)
-            .header("CK", &project().project_key)
-            .json(&edit_device)
-            .send()
-            .await;
+        let result = api.update_device(&device().id, &edit_device).await;
 
         match result {
-            Ok(data) => {
-                let text = data
-                    .text()
-                    .await
-                    .unwrap_or_else(|_| "Error parse String".to_string());
+            Ok(text) => {
                 toastapi.success(
                     "Updated".to_string(),
                     ToastOptions::new()
@@ -706,30 +2105,79 @@ pub fn DeviceAttrPanelImpl(
     rsx! {
         div { class: "grid grid-cols-[1fr_auto] items-center mt-8",
             h1 { class: "text-2xl font-bold", "Device Info" }
-            Button { variant: if is_device_dirty() { ButtonVariant::Primary } else { ButtonVariant::Secondary },
-                "Save(TODO)"
+            Button {
+                variant: if is_device_dirty() { ButtonVariant::Primary } else { ButtonVariant::Secondary },
+                disabled: device_errors().is_some(),
+                onclick: save_device,
+                "Save"
             }
         }
         div { class: "grid grid-cols-[auto_auto] gap-2 w-full",
             div { "ID" }
             div { {device().id} }
             div { "Type" }
-            div { {device().kind} }
-            div { "Name" }
             div {
                 Input {
                     class: "input w-full",
-                    oninput: move |i: FormEvent| { device_info.write().name = i.value() },
-                    value: device_info().name,
+                    oninput: move |i: FormEvent| { device_info.write().kind = i.value() },
+                    value: device_info().kind,
+                }
+            }
+            div { "Name" }
+            div {
+                Input {
+                    class: "input w-full",
+                    oninput: move |i: FormEvent| { device_info.write().name = i.value() },
+                    value: device_info().name,
                 }
             }
             div { "Desc" }
             div {
                 Textarea {
-                    oninput: move |i: FormEvent| { device_info.write().desc = Some(i.value()) },
+                    oninput: move |i: FormEvent| { device_info.write().desc = i.value() },
                     value: device_info().desc,
                 }
             }
+            div { "URI" }
+            div {
+                Input {
+                    class: "input w-full",
+                    placeholder: "https://example.com/device",
+                    oninput: move |i: FormEvent| { device_info.write().uri = i.value() },
+                    value: device_info().uri,
+                }
+            }
+            div { "Latitude" }
+            div {
+                Input {
+                    class: "input w-full",
+                    r#type: "number",
+                    step: "any",
+                    min: "-90",
+                    max: "90",
+                    oninput: move |i: FormEvent| { device_info.write().lat = i.value() },
+                    value: device_info().lat,
+                }
+            }
+            div { "Longitude" }
+            div {
+                Input {
+                    class: "input w-full",
+                    r#type: "number",
+                    step: "any",
+                    min: "-180",
+                    max: "180",
+                    oninput: move |i: FormEvent| { device_info.write().lon = i.value() },
+                    value: device_info().lon,
+                }
+            }
+        }
+        if let Some(errors) = device_errors() {
+            ul { class: "text-red-500 text-sm mt-2",
+                for error in errors {
+                    li { {error} }
+                }
+            }
         }
 
         div { class: "grid grid-cols-[1fr_auto] items-center mt-8",
@@ -789,7 +2237,7 @@ pub fn DeviceAttrPanelImpl(
             }
         }
 
-        MonitorPanel { project, endpoint, device }
+        MonitorPanel { project_name, device }
     }
 }
 
@@ -800,22 +2248,18 @@ pub fn SensorAttrPanel(
     device: Memo<Option<Device>>,
     sensor: Memo<Option<Sensor>>,
     ctx: Store<PageContext>,
-    project_meta: Resource<Result<Vec<Device>>>,
+    project_meta: Resource<ApiResult<Vec<Device>>>,
 ) -> Element {
     let panel = if project().is_some()
         && endpoint().is_some()
         && device().is_some()
         && sensor().is_some()
     {
-        let project = project().unwrap();
-        let endpoint = endpoint().unwrap();
         let device = device().unwrap();
         let sensor = sensor().unwrap();
 
         rsx! {
             SensorAttrPanelImpl {
-                project,
-                endpoint,
                 device,
                 sensor,
                 ctx,
@@ -894,42 +2338,196 @@ pub fn SensorHeader(
     }
 }
 
+/// Shows what a formula being edited turns the sensor's latest raw values into.
+#[component]
+fn FormulaPreview(formula: String, latest: Option<Vec<String>>) -> Element {
+    if formula.trim().is_empty() {
+        return rsx! {
+            p { class: "text-sm text-gray-500",
+                "Operators + - * / % ^, value[n], pi, e and functions such as abs, sqrt, ln, log, round, min, max."
+            }
+        };
+    }
+    let result = match (&latest, Formula::parse(&formula)) {
+        (_, Err(e)) => Err(e.to_string()),
+        (None, Ok(_)) => Ok("No raw data to preview with".to_string()),
+        (Some(values), Ok(parsed)) => parsed
+            .eval(values)
+            .map(|v| format!("[{}] → {v}", values.join(", ")))
+            .map_err(|e| e.to_string()),
+    };
+    match result {
+        Ok(text) => rsx! {
+            p { class: "text-sm text-gray-500", "Preview: {text}" }
+        },
+        Err(error) => rsx! {
+            p { class: "text-sm text-red-500", "{error}" }
+        },
+    }
+}
+
+/// Keeps the original optional value when its text was not edited, so untouched fields
+/// (including ones the server sent as empty strings) round-trip unchanged. A cleared field is
+/// sent as an empty string, since the server reads a missing one as unchanged.
+fn edited_optional(original: &Option<String>, text: &str) -> Option<String> {
+    let trimmed = text.trim();
+    if original.as_deref().unwrap_or_default() == text {
+        original.clone()
+    } else if trimmed.is_empty() && original.is_none() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+/// Checks that an edited URI is absolute, such as `https://example.com/manual`. Like formulas,
+/// URIs already saved are kept as they are.
+fn check_uri(original: &Option<String>, text: &str) -> Result<(), String> {
+    let text = text.trim();
+    if text.is_empty() || text == original.as_deref().unwrap_or_default().trim() {
+        return Ok(());
+    }
+    Url::parse(text).map(|_| ()).map_err(|e| format!("URI: {e}"))
+}
+
+/// Editable text of the "Sensor Info" form.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SensorForm {
+    name: String,
+    kind: SensorType,
+    desc: String,
+    uri: String,
+    formula: String,
+}
+
+impl SensorForm {
+    fn from_sensor(sensor: &Sensor) -> Self {
+        SensorForm {
+            name: sensor.name.clone(),
+            kind: sensor.kind.clone(),
+            desc: sensor.desc.clone().unwrap_or_default(),
+            uri: sensor.uri.clone().unwrap_or_default(),
+            formula: sensor.formula.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Validates the form and builds the update request from the saved sensor.
+    fn to_edit_sensor(&self, sensor: &Sensor) -> Result<EditSensor, Vec<String>> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push("Name is required".to_string());
+        }
+        if let Err(e) = check_uri(&sensor.uri, &self.uri) {
+            errors.push(e);
+        }
+        // Formulas already saved are kept even if this viewer cannot parse them.
+        let formula = self.formula.trim();
+        if !formula.is_empty() && !self.kind.has_formula() {
+            errors.push(format!("Formula: {} sensors have no formula", self.kind));
+        } else if !formula.is_empty()
+            && formula != sensor.formula.as_deref().unwrap_or_default().trim()
+        {
+            if let Err(e) = Formula::parse(formula) {
+                errors.push(format!("Formula: {e}"));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(EditSensor {
+            id: None,
+            name: self.name.trim().to_string(),
+            kind: self.kind.clone(),
+            desc: edited_optional(&sensor.desc, &self.desc),
+            uri: edited_optional(&sensor.uri, &self.uri),
+            formula: edited_optional(&sensor.formula, &self.formula),
+            attributes: sensor.attributes.clone(),
+        })
+    }
+}
+
 #[component]
 pub fn SensorAttrPanelImpl(
-    project: ReadSignal<Project>,
-    endpoint: ReadSignal<Endpoint>,
     device: ReadSignal<Device>,
     sensor: ReadSignal<Sensor>,
     ctx: Store<PageContext>,
-    project_meta: Resource<Result<Vec<Device>>>,
+    project_meta: Resource<ApiResult<Vec<Device>>>,
 ) -> Element {
     let mut attributes = use_signal(|| sensor().attributes.unwrap_or_default().clone());
     let is_dirty = use_memo(move || attributes() != sensor().attributes.unwrap_or_default());
 
+    let api = use_api();
+    let latest_values = use_resource(move || async move {
+        let api = api()?;
+        let rows = api.rawdata(&device().id).await.ok()?;
+        let sensor_id = sensor().id;
+        rows.into_iter().find(|r| r.id == sensor_id).map(|r| r.value)
+    });
+    let latest_values = move || latest_values.read().clone().flatten();
+
+    let mut sensor_info = use_signal(|| SensorForm::from_sensor(&sensor()));
+    let is_sensor_dirty = use_memo(move || sensor_info() != SensorForm::from_sensor(&sensor()));
+    let sensor_errors = use_memo(move || sensor_info().to_edit_sensor(&sensor()).err());
+
+    let save_sensor = move |_| async move {
+        let Some(api) = api() else {
+            return;
+        };
+        let toastapi = use_toast();
+        let edit_sensor = match sensor_info().to_edit_sensor(&sensor()) {
+            Ok(edit_sensor) => edit_sensor,
+            Err(errors) => {
+                toastapi.error(
+                    "Invalid sensor info".to_string(),
+                    ToastOptions::new()
+                        .description(errors.join(", "))
+                        .duration(Duration::from_secs(10)),
+                );
+                return;
+            }
+        };
+
+        let result = api
+            .update_sensor(&device().id, &sensor().id, &edit_sensor)
+            .await;
+
+        match result {
+            Ok(text) => {
+                toastapi.success(
+                    "Updated".to_string(),
+                    ToastOptions::new()
+                        .description(text)
+                        .duration(Duration::from_secs(5)),
+                );
+                project_meta.restart();
+            }
+            Err(e) => {
+                toastapi.error(
+                    "Update Failed".to_string(),
+                    ToastOptions::new()
+                        .description(format!("{e}"))
+                        .duration(Duration::from_secs(10)),
+                );
+            }
+        }
+    };
+
     let save_attrs = move |_| async move {
+        let Some(api) = api() else {
+            return;
+        };
         let edit_sensor = EditSensor {
-            name: sensor().name.clone(),
-            kind: sensor().kind.clone(),
             attributes: Some(attributes().clone()),
-            ..Default::default()
+            ..EditSensor::from(&sensor())
         };
-        let client = Client::new();
-        let url = endpoint().sensor(&device().id, &sensor().id);
         let toastapi = use_toast();
 
-        let result = client
-            .put(url)
-            .header("CK", &project().project_key)
-            .json(&edit_sensor)
-            .send()
+        let result = api
+            .update_sensor(&device().id, &sensor().id, &edit_sensor)
             .await;
 
         match result {
-            Ok(data) => {
-                let text = data
-                    .text()
-                    .await
-                    .unwrap_or_else(|_| "Error parse String".to_string());
+            Ok(text) => {
                 toastapi.success(
                     "Updated".to_string(),
                     ToastOptions::new()
@@ -950,6 +2548,71 @@ pub fn SensorAttrPanelImpl(
     };
 
     rsx! {
+        Card { class: "mb-8",
+            CardHeader {
+                CardTitle { "Sensor Info" }
+                CardAction {
+                    Button {
+                        variant: if is_sensor_dirty() { ButtonVariant::Primary } else { ButtonVariant::Secondary },
+                        disabled: sensor_errors().is_some(),
+                        onclick: save_sensor,
+                        "Save"
+                    }
+                }
+            }
+            CardContent {
+                div { class: "grid grid-cols-[auto_1fr] gap-2 items-center w-full",
+                    div { "ID" }
+                    div { {sensor().id} }
+                    div { "Name" }
+                    Input {
+                        class: "input w-full",
+                        oninput: move |i: FormEvent| { sensor_info.write().name = i.value() },
+                        value: sensor_info().name,
+                    }
+                    div { "Type" }
+                    RadioGroup {
+                        value: sensor_info().kind.as_str().to_string(),
+                        horizontal: true,
+                        on_value_change: move |v: String| {
+                            if let Some(kind) = SensorType::from_api_name(&v) {
+                                sensor_info.write().kind = kind;
+                            }
+                        },
+                        for (i , kind) in SensorType::ALL.into_iter().enumerate() {
+                            RadioItem { index: i, value: kind.as_str().to_string(), "{kind}" }
+                        }
+                    }
+                    div { "Desc" }
+                    Textarea {
+                        oninput: move |i: FormEvent| { sensor_info.write().desc = i.value() },
+                        value: sensor_info().desc,
+                    }
+                    div { "URI" }
+                    Input {
+                        class: "input w-full",
+                        oninput: move |i: FormEvent| { sensor_info.write().uri = i.value() },
+                        value: sensor_info().uri,
+                    }
+                    div { "Formula" }
+                    Input {
+                        class: "input w-full",
+                        placeholder: "value[0] * 0.1",
+                        oninput: move |i: FormEvent| { sensor_info.write().formula = i.value() },
+                        value: sensor_info().formula,
+                    }
+                    div {}
+                    FormulaPreview { formula: sensor_info().formula, latest: latest_values() }
+                }
+                if let Some(errors) = sensor_errors() {
+                    ul { class: "text-red-500 text-sm mt-2",
+                        for error in errors {
+                            li { {error} }
+                        }
+                    }
+                }
+            }
+        }
         Card {
             CardHeader {
                 CardTitle { "Attributes" }
@@ -1009,59 +2672,345 @@ pub fn SensorAttrPanelImpl(
                 }
             }
         }
+
+        if sensor().kind == SensorType::Snapshot {
+            SnapshotGallery { device, sensor }
+        }
+        SensorHistoryPanel { device, sensor }
     }
 }
 
+/// Converts a `datetime-local` input value (`2024-01-31T08:00`), read as a time in `zone`, into
+/// an ISO-8601 UTC time.
+pub(super) fn datetime_input_to_iso(value: &str, zone: TimeZoneChoice) -> Option<String> {
+    let time = ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())?;
+    Some(zone.to_utc(time)?.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
 #[component]
-pub fn MonitorPanel(
-    project: ReadSignal<Project>,
-    endpoint: ReadSignal<Endpoint>,
-    device: ReadSignal<Device>,
-) -> Element {
-    let active_status = use_resource(move || async move {
-        let client = reqwest::Client::new();
-        let url = endpoint().active(&device().id);
-        client
-            .get(url)
-            .header("CK", project().project_key.as_str())
-            .send()
-            .await?
-            .json::<Option<ActiveInfo>>()
-            .await
+pub fn SensorHistoryPanel(device: ReadSignal<Device>, sensor: ReadSignal<Sensor>) -> Element {
+    let api = use_api();
+    let mut start = use_signal(String::new);
+    let mut end = use_signal(String::new);
+    let mut limit = use_signal(|| "100".to_string());
+    let mut query = use_signal(|| None::<RawDataQuery>);
+    let format = use_display_format(sensor);
+    let preferences = use_context::<Signal<Preferences>>();
+
+    let mut history: Resource<ApiResult<Vec<RawData>>> = use_resource(move || async move {
+        let Some(query) = query() else {
+            return Ok(Vec::new());
+        };
+        let api = api().ok_or(ApiError::NotConfigured)?;
+        api.sensor_rawdata(&device().id, &sensor().id, &query).await
+    });
+
+    let on_query = move |_| {
+        let zone = preferences().time_zone;
+        query.set(Some(RawDataQuery {
+            start: datetime_input_to_iso(&start(), zone),
+            end: datetime_input_to_iso(&end(), zone),
+            limit: limit().trim().parse().ok(),
+        }));
+    };
+
+    let rows_rsx = if query().is_none() {
+        rsx! {
+            p { "Pick a time range and press Query." }
+        }
+    } else if let Some(response) = &*history.read() {
+        match response {
+            Ok(rows) if rows.is_empty() => rsx! {
+                p { "No data in this range" }
+            },
+            Ok(rows) => rsx! {
+                if sensor().kind == SensorType::Gauge {
+                    GaugeChart { points: gauge_points(rows, &sensor(), &format()), format: format() }
+                }
+                table { class: "w-full text-left",
+                    thead {
+                        tr {
+                            th { class: "pr-4", "Time" }
+                            th { "Value" }
+                        }
+                    }
+                    tbody {
+                        for row in rows.iter() {
+                            tr { class: "border-t",
+                                td { class: "py-1 pr-4 whitespace-nowrap",
+                                    TimeLabel { time: row.time }
+                                }
+                                td { class: "py-1 break-all", {display_value(&sensor(), &format(), &row.value)} }
+                            }
+                        }
+                    }
+                }
+            },
+            Err(error) => rsx! {
+                ApiErrorCard {
+                    error: error.clone(),
+                    on_retry: move |_| history.restart(),
+                }
+            },
+        }
+    } else {
+        rsx! {
+            p { "Loading" }
+        }
+    };
+
+    rsx! {
+        Card { class: "mt-8",
+            CardHeader {
+                CardTitle { "History" }
+                CardDescription {
+                    "Values reported by this sensor. Start and end use the time zone from Settings ({preferences().time_zone})."
+                }
+            }
+            CardContent {
+                div { class: "flex flex-wrap gap-4 items-end mb-4",
+                    div { class: "flex flex-col gap-2",
+                        Label { html_for: "history_start", "Start" }
+                        Input {
+                            id: "history_start",
+                            r#type: "datetime-local",
+                            value: start(),
+                            oninput: move |e: FormEvent| start.set(e.value()),
+                        }
+                    }
+                    div { class: "flex flex-col gap-2",
+                        Label { html_for: "history_end", "End" }
+                        Input {
+                            id: "history_end",
+                            r#type: "datetime-local",
+                            value: end(),
+                            oninput: move |e: FormEvent| end.set(e.value()),
+                        }
+                    }
+                    div { class: "flex flex-col gap-2",
+                        Label { html_for: "history_limit", "Limit" }
+                        Input {
+                            id: "history_limit",
+                            r#type: "number",
+                            min: "1",
+                            value: limit(),
+                            oninput: move |e: FormEvent| limit.set(e.value()),
+                        }
+                    }
+                    Button { onclick: on_query, "Query" }
+                    ExportDialog {
+                        device,
+                        sensors: device().sensors.unwrap_or_default(),
+                        selected: vec![sensor().id],
+                    }
+                }
+                {rows_rsx}
+            }
+        }
+    }
+}
+
+/// Seconds between active status polls while the monitor panel is open.
+const ACTIVE_POLL_SECS: u64 = 60;
+
+/// Report windows offered by [`ActiveStatusReport`].
+const REPORT_WINDOWS: [(&str, &str); 4] = [
+    ("24h", "24 hours"),
+    ("7d", "7 days"),
+    ("30d", "30 days"),
+    ("month", "This month"),
+];
+
+/// Start and end, in Unix milliseconds, of a report window ending at `now`; months start at
+/// midnight in `zone`.
+fn report_window(window: &str, now: DateTime<Utc>, zone: TimeZoneChoice) -> (i64, i64) {
+    let start = match window {
+        "24h" => now - ChronoDuration::hours(24),
+        "30d" => now - ChronoDuration::days(30),
+        "month" => zone
+            .local_time(now)
+            .date()
+            .with_day(1)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .and_then(|d| zone.to_utc(d))
+            .unwrap_or(now),
+        _ => now - ChronoDuration::days(7),
+    };
+    (start.timestamp_millis(), now.timestamp_millis())
+}
+
+fn status_color(status: ActiveStatus) -> &'static str {
+    match status {
+        ActiveStatus::Online => "bg-green-500",
+        ActiveStatus::Offline => "bg-red-500",
+        ActiveStatus::Abnormal => "bg-amber-500",
+        ActiveStatus::Start => "bg-sky-500",
+        ActiveStatus::Stop | ActiveStatus::Unset => "bg-gray-400",
+    }
+}
+
+/// Formats a duration in milliseconds with its two largest units, e.g. `2d 3h`.
+fn format_duration_ms(ms: i64) -> String {
+    let secs = ms / 1000;
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+fn format_time_ms(ms: i64, preferences: &Preferences) -> String {
+    DateTime::from_timestamp_millis(ms)
+        .map(|t| preferences.format_time(&Timestamp(t)))
+        .unwrap_or_default()
+}
+
+/// Timeline and availability summary of the statuses recorded while polling this device.
+#[component]
+fn ActiveStatusReport(project_name: ReadSignal<String>, device: ReadSignal<Device>) -> Element {
+    let status_history = use_context::<Signal<StatusHistory>>();
+    let preferences = use_context::<Signal<Preferences>>();
+    let mut window = use_signal(|| "7d".to_string());
+
+    let (from, to) = report_window(&window(), Utc::now(), preferences.read().time_zone);
+    let segments: Vec<StatusSegment> = status_history
+        .read()
+        .get(&status_key(&project_name(), &device().id))
+        .map(|segments| {
+            segments
+                .iter()
+                .filter(|s| s.end >= from && s.start <= to)
+                .copied()
+                .collect()
+        })
+        .unwrap_or_default();
+    let report = UptimeReport::new(&segments, from, to);
+    let span = (to - from).max(1) as f64;
+    let bars = segments.iter().map(|s| {
+        let start = s.start.max(from);
+        let left = (start - from) as f64 * 100.0 / span;
+        let width = ((s.end.min(to) - start) as f64 * 100.0 / span).max(0.2);
+        let title = format!(
+            "{}: {} - {}",
+            s.status,
+            format_time_ms(s.start, &preferences()),
+            format_time_ms(s.end, &preferences())
+        );
+        (left, width, status_color(s.status), title)
     });
+    let uptime = report
+        .uptime_percent()
+        .map(|p| format!("{p:.2}%"))
+        .unwrap_or_else(|| "-".to_string());
+    let coverage = report.observed as f64 * 100.0 / span;
 
-    let active_setting = use_resource(move || async move {
-        let client = reqwest::Client::new();
-        let url = endpoint().active_setting(&device().id);
-        client
-            .get(url)
-            .header("CK", project().project_key.as_str())
-            .send()
-            .await?
-            .json::<ActiveDevice>()
-            .await
+    rsx! {
+        div { class: "grid grid-cols-[1fr_auto] items-center mt-8",
+            h1 { class: "text-2xl font-bold", "Availability" }
+            RadioGroup {
+                value: window(),
+                horizontal: true,
+                on_value_change: move |v: String| window.set(v),
+                for (i , (value , label)) in REPORT_WINDOWS.into_iter().enumerate() {
+                    RadioItem { index: i, value, {label} }
+                }
+            }
+        }
+        div { class: "relative h-6 w-full rounded bg-gray-200 overflow-hidden",
+            for (left , width , color , title) in bars {
+                div {
+                    class: "absolute top-0 h-full {color}",
+                    style: "left: {left}%; width: {width}%;",
+                    title,
+                }
+            }
+        }
+        div { class: "flex justify-between text-xs text-gray-500 mt-1",
+            span { {format_time_ms(from, &preferences())} }
+            span { {format_time_ms(to, &preferences())} }
+        }
+        div { class: "flex flex-wrap gap-4 text-xs mt-2",
+            for status in [ActiveStatus::Online, ActiveStatus::Offline, ActiveStatus::Abnormal, ActiveStatus::Stop] {
+                span { class: "flex items-center gap-1",
+                    span { class: "inline-block w-3 h-3 rounded {status_color(status)}" }
+                    "{status}"
+                }
+            }
+            span { class: "flex items-center gap-1",
+                span { class: "inline-block w-3 h-3 rounded bg-gray-200" }
+                "Not recorded"
+            }
+        }
+        div { class: "grid grid-cols-[auto_auto] gap-2 mt-4",
+            div { "Uptime:" }
+            div { class: "font-bold", {uptime} }
+            div { "Outages:" }
+            div { "{report.outages}" }
+            div { "Downtime:" }
+            div { {format_duration_ms(report.outage)} }
+            div { "Recorded:" }
+            div { "{coverage:.1}% of window" }
+        }
+        p { class: "text-xs text-gray-500 mt-2",
+            "Statuses are recorded while this page is open; unrecorded time is excluded from uptime."
+        }
+    }
+}
+
+#[component]
+pub fn MonitorPanel(project_name: ReadSignal<String>, device: ReadSignal<Device>) -> Element {
+    let api = use_api();
+    let mut active_status = use_resource(move || async move {
+        let api = api().ok_or(ApiError::NotConfigured)?;
+        api.active(&device().id).await
+    });
+
+    let mut active_setting = use_resource(move || async move {
+        let api = api().ok_or(ApiError::NotConfigured)?;
+        api.active_setting(&device().id).await
+    });
+
+    let mut active_notify = use_resource(move || async move {
+        let api = api().ok_or(ApiError::NotConfigured)?;
+        api.active_notify(&device().id).await
+    });
+
+    use_future(move || async move {
+        loop {
+            sleep(Duration::from_secs(ACTIVE_POLL_SECS)).await;
+            if active_status.finished() {
+                active_status.restart();
+            }
+        }
     });
 
-    let active_notify = use_resource(move || async move {
-        let client = reqwest::Client::new();
-        let url = endpoint().active_notify(&device().id);
-        client
-            .get(url)
-            .header("CK", project().project_key.as_str())
-            .send()
-            .await?
-            .json::<Vec<ActiveNotify>>()
-            .await
+    let mut status_history = use_context::<Signal<StatusHistory>>();
+    use_effect(move || {
+        if let Some(Ok(Some(info))) = &*active_status.read() {
+            let now = Utc::now().timestamp_millis();
+            let mut history = status_history.write();
+            let key = status_key(&project_name(), &device().id);
+            record_status(history.entry(key).or_default(), info.status, now);
+        }
     });
 
-    let active_rsx = if let Some(active_status) = &*active_status.read() {
-        match active_status {
+    let active_rsx = if let Some(response) = &*active_status.read() {
+        match response {
             Ok(active_status) => {
                 if let Some(active_status) = active_status {
                     rsx! {
                         div {
                             p { "{active_status.status}" }
-                            p { "{active_status.create_time}" }
+                            p {
+                                TimeLabel { time: active_status.create_time }
+                            }
                         }
                     }
                 } else {
@@ -1070,8 +3019,11 @@ pub fn MonitorPanel(
                     }
                 }
             }
-            Err(_) => rsx! {
-                p { "Load Error" }
+            Err(error) => rsx! {
+                ApiErrorCard {
+                    error: error.clone(),
+                    on_retry: move |_| active_status.restart(),
+                }
             },
         }
     } else {
@@ -1080,28 +3032,21 @@ pub fn MonitorPanel(
         }
     };
 
-    let active_setting_rsx = if let Some(active_setting) = &*active_setting.read() {
-        match active_setting {
-            Ok(setting) => {
-                rsx! {
-                    div { class: "grid grid-cols-[auto_auto] gap-2",
-                        div { "Device ID:" }
-                        div { {setting.device_id.clone()} }
-                        div { "Enabled:" }
-                        div { "{setting.enable}" }
-                        div { "Period:" }
-                        div { {setting.period.clone()} }
-                        div { "Min Uploads:" }
-                        div { {setting.min_uploads.map(|v| v.to_string()).unwrap_or_default()} }
-                        div { "Max Uploads:" }
-                        div { {setting.max_uploads.map(|v| v.to_string()).unwrap_or_default()} }
-                        div { "Created:" }
-                        div { "{setting.create_time.unwrap_or_default()}" }
-                    }
+    let active_setting_rsx = if let Some(response) = &*active_setting.read() {
+        match response {
+            Ok(setting) => rsx! {
+                ActiveSettingEditor {
+                    key: "{setting.device_id}-{setting.create_time.map(|t| t.millis()).unwrap_or_default()}",
+                    device,
+                    setting: setting.clone(),
+                    on_saved: move |_| active_setting.restart(),
+                }
+            },
+            Err(error) => rsx! {
+                ApiErrorCard {
+                    error: error.clone(),
+                    on_retry: move |_| active_setting.restart(),
                 }
-            }
-            Err(_) => rsx! {
-                p { "Load Error" }
             },
         }
     } else {
@@ -1110,13 +3055,20 @@ pub fn MonitorPanel(
         }
     };
 
-    let active_notify_rsx = if let Some(active_notify) = &*active_notify.read() {
-        match active_notify {
+    let active_notify_rsx = if let Some(response) = &*active_notify.read() {
+        match response {
             Ok(notifies) => rsx! {
-                ActiveNotifySection { notifies: notifies.clone() }
+                ActiveNotifySection {
+                    device,
+                    notifies: notifies.clone(),
+                    on_changed: move |_| active_notify.restart(),
+                }
             },
-            Err(_) => rsx! {
-                p { "Load Error" }
+            Err(error) => rsx! {
+                ApiErrorCard {
+                    error: error.clone(),
+                    on_retry: move |_| active_notify.restart(),
+                }
             },
         }
     } else {
@@ -1131,10 +3083,8 @@ pub fn MonitorPanel(
         }
         {active_rsx}
 
-        div { class: "grid grid-cols-[1fr_auto] items-center mt-8",
-            h1 { class: "text-2xl font-bold", "Active Setting" }
-            Button { "Save(TODO)" }
-        }
+        ActiveStatusReport { project_name, device }
+
         {active_setting_rsx}
 
         div { class: "grid grid-cols-[1fr_auto] items-center mt-8",
@@ -1145,15 +3095,404 @@ pub fn MonitorPanel(
     }
 }
 
+/// Units accepted in an active monitor period, e.g. `30s`, `5m`, `1h` or `1d`.
+const PERIOD_UNITS: [(char, u64); 4] = [('s', 1), ('m', 60), ('h', 3600), ('d', 86400)];
+
+/// Common periods offered next to the period input.
+const PERIOD_PRESETS: [&str; 6] = ["1m", "5m", "15m", "30m", "1h", "1d"];
+
+/// Parses a period such as `5m` into seconds.
+fn parse_period(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let invalid = || format!("Period '{text}' must be a number followed by s, m, h or d");
+    let unit = text.chars().last().ok_or("Period is required")?;
+    let (_, scale) = PERIOD_UNITS
+        .iter()
+        .find(|(u, _)| *u == unit)
+        .ok_or_else(invalid)?;
+    match text[..text.len() - 1].parse::<u64>() {
+        Ok(0) => Err("Period must be greater than zero".to_string()),
+        Ok(n) => n
+            .checked_mul(*scale)
+            .ok_or_else(|| format!("Period '{text}' is too long")),
+        Err(_) => Err(invalid()),
+    }
+}
+
+/// Parses an optional, non-negative upload count.
+fn parse_uploads(text: &str, name: &str) -> Result<Option<i32>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    match text.parse::<i32>() {
+        Ok(v) if v >= 0 => Ok(Some(v)),
+        _ => Err(format!("{name} must be a whole number of at least 0")),
+    }
+}
+
+/// Editable text of the "Active Setting" form.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct ActiveSettingForm {
+    enable: bool,
+    period: String,
+    min_uploads: String,
+    max_uploads: String,
+}
+
+impl ActiveSettingForm {
+    fn from_setting(setting: &ActiveDevice) -> Self {
+        ActiveSettingForm {
+            enable: setting.enable,
+            period: setting.period.clone(),
+            min_uploads: setting.min_uploads.map(|v| v.to_string()).unwrap_or_default(),
+            max_uploads: setting.max_uploads.map(|v| v.to_string()).unwrap_or_default(),
+        }
+    }
+
+    fn to_edit_setting(&self) -> Result<EditActiveDevice, Vec<String>> {
+        let mut errors = Vec::new();
+        if let Err(e) = parse_period(&self.period) {
+            errors.push(e);
+        }
+        let min_uploads = parse_uploads(&self.min_uploads, "Min uploads").unwrap_or_else(|e| {
+            errors.push(e);
+            None
+        });
+        let max_uploads = parse_uploads(&self.max_uploads, "Max uploads").unwrap_or_else(|e| {
+            errors.push(e);
+            None
+        });
+        if let (Some(min), Some(max)) = (min_uploads, max_uploads) {
+            if min > max {
+                errors.push("Min uploads must not be greater than max uploads".to_string());
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(EditActiveDevice {
+            enable: self.enable,
+            period: self.period.trim().to_string(),
+            min_uploads,
+            max_uploads,
+        })
+    }
+}
+
+#[component]
+fn ActiveSettingEditor(
+    device: ReadSignal<Device>,
+    setting: ActiveDevice,
+    on_saved: EventHandler<()>,
+) -> Element {
+    let original = ActiveSettingForm::from_setting(&setting);
+    let mut form = use_signal(|| original.clone());
+    let is_dirty = form() != original;
+    let errors = form().to_edit_setting().err();
+
+    let api = use_api();
+    let save_setting = move |_| async move {
+        let Some(api) = api() else {
+            return;
+        };
+        let toastapi = use_toast();
+        let edit_setting = match form().to_edit_setting() {
+            Ok(edit_setting) => edit_setting,
+            Err(errors) => {
+                toastapi.error(
+                    "Invalid active setting".to_string(),
+                    ToastOptions::new()
+                        .description(errors.join(", "))
+                        .duration(Duration::from_secs(10)),
+                );
+                return;
+            }
+        };
+
+        match api.update_active_setting(&device().id, &edit_setting).await {
+            Ok(text) => {
+                toastapi.success(
+                    "Updated".to_string(),
+                    ToastOptions::new()
+                        .description(text)
+                        .duration(Duration::from_secs(5)),
+                );
+                on_saved.call(());
+            }
+            Err(e) => {
+                toastapi.error(
+                    "Update Failed".to_string(),
+                    ToastOptions::new()
+                        .description(format!("{e}"))
+                        .duration(Duration::from_secs(10)),
+                );
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "grid grid-cols-[1fr_auto] items-center mt-8",
+            h1 { class: "text-2xl font-bold", "Active Setting" }
+            Button {
+                variant: if is_dirty { ButtonVariant::Primary } else { ButtonVariant::Secondary },
+                disabled: errors.is_some(),
+                onclick: save_setting,
+                "Save"
+            }
+        }
+        div { class: "grid grid-cols-[auto_1fr] gap-2 items-center",
+            div { "Device ID:" }
+            div { {setting.device_id.clone()} }
+            div { "Enabled:" }
+            Switch {
+                checked: Some(form().enable),
+                on_checked_change: move |v| form.write().enable = v,
+                aria_label: "Enable active monitor",
+            }
+            div { "Period:" }
+            div { class: "flex flex-wrap gap-2 items-center",
+                Input {
+                    class: "input w-24",
+                    placeholder: "5m",
+                    value: form().period,
+                    oninput: move |e: FormEvent| form.write().period = e.value(),
+                }
+                for preset in PERIOD_PRESETS {
+                    Button {
+                        variant: if form().period.trim() == preset { ButtonVariant::Primary } else { ButtonVariant::Outline },
+                        onclick: move |_| form.write().period = preset.to_string(),
+                        {preset}
+                    }
+                }
+            }
+            div { "Min Uploads:" }
+            Input {
+                class: "input w-full",
+                r#type: "number",
+                min: "0",
+                step: "1",
+                value: form().min_uploads,
+                oninput: move |e: FormEvent| form.write().min_uploads = e.value(),
+            }
+            div { "Max Uploads:" }
+            Input {
+                class: "input w-full",
+                r#type: "number",
+                min: "0",
+                step: "1",
+                value: form().max_uploads,
+                oninput: move |e: FormEvent| form.write().max_uploads = e.value(),
+            }
+            div { "Created:" }
+            div {
+                TimeLabel { time: setting.create_time }
+            }
+        }
+        if let Some(errors) = errors {
+            ul { class: "text-red-500 text-sm mt-2",
+                for error in errors {
+                    li { {error} }
+                }
+            }
+        }
+    }
+}
+
+/// Notification channels the form knows how to validate.
+const NOTIFY_KINDS: [(&str, &str); 2] = [("email", "Email"), ("webhook", "Webhook")];
+
+/// Label, placeholder and check for `ActiveNotifySetting.to` of a notification kind.
+fn notify_target(kind: &str) -> (&'static str, &'static str) {
+    match kind {
+        "email" => ("Email", "ops@example.com"),
+        "webhook" => ("Webhook URL", "https://example.com/hook"),
+        _ => ("To", ""),
+    }
+}
+
+fn validate_notify_target(kind: &str, to: &str) -> Result<(), String> {
+    let to = to.trim();
+    if to.is_empty() {
+        return Err(format!("{} is required", notify_target(kind).0));
+    }
+    match kind {
+        "email" => {
+            let valid = to.split(',').map(str::trim).all(|address| {
+                address
+                    .split_once('@')
+                    .is_some_and(|(user, host)| !user.is_empty() && host.contains('.'))
+            });
+            if valid {
+                Ok(())
+            } else {
+                Err(format!("'{to}' is not a valid email address"))
+            }
+        }
+        "webhook" => {
+            if to.starts_with("https://") || to.starts_with("http://") {
+                Ok(())
+            } else {
+                Err("Webhook URL must start with http:// or https://".to_string())
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Editable text of the notification dialog.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotifyForm {
+    enable: bool,
+    name: String,
+    kind: String,
+    to: String,
+    message: String,
+}
+
+impl Default for NotifyForm {
+    fn default() -> Self {
+        NotifyForm {
+            enable: true,
+            name: String::new(),
+            kind: NOTIFY_KINDS[0].0.to_string(),
+            to: String::new(),
+            message: String::new(),
+        }
+    }
+}
+
+impl NotifyForm {
+    fn from_notify(notify: &ActiveNotify) -> Self {
+        NotifyForm {
+            enable: notify.enable,
+            name: notify.name.clone(),
+            kind: notify.kind.clone(),
+            to: notify.setting.to.clone(),
+            message: notify.setting.message.clone().unwrap_or_default(),
+        }
+    }
+
+    fn to_edit_notify(&self) -> Result<EditActiveNotify, Vec<String>> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push("Name is required".to_string());
+        }
+        if let Err(e) = validate_notify_target(&self.kind, &self.to) {
+            errors.push(e);
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(EditActiveNotify {
+            enable: self.enable,
+            name: self.name.trim().to_string(),
+            kind: self.kind.clone(),
+            setting: ActiveNotifySetting {
+                to: self.to.trim().to_string(),
+                // An empty message clears the saved one.
+                message: Some(if self.message.trim().is_empty() {
+                    String::new()
+                } else {
+                    self.message.clone()
+                }),
+            },
+        })
+    }
+}
+
+#[derive(Store)]
+pub struct NotifyCtx {
+    pub is_open: bool,
+    /// The notification being edited, or `None` when adding a new one.
+    pub editing: Option<i32>,
+    pub form: NotifyForm,
+}
+
+#[store]
+impl<Lens> Store<NotifyCtx, Lens> {
+    fn open_new(&mut self) {
+        self.editing().set(None);
+        self.form().set(NotifyForm::default());
+        self.is_open().set(true);
+    }
+
+    fn open_edit(&mut self, notify: &ActiveNotify) {
+        self.editing().set(Some(notify.id));
+        self.form().set(NotifyForm::from_notify(notify));
+        self.is_open().set(true);
+    }
+}
+
 #[component]
-fn ActiveNotifyCard(notify: ActiveNotify) -> Element {
+fn ActiveNotifyCard(
+    device: ReadSignal<Device>,
+    notify: ActiveNotify,
+    notify_ctx: Store<NotifyCtx>,
+    delete_ctx: Store<DeleteCtx>,
+    on_changed: EventHandler<()>,
+) -> Element {
+    let mut toggling = use_signal(|| false);
+
+    let api = use_api();
+    let notify_clone = notify.clone();
+    let toggle_enable = move |enable: bool| {
+        let notify = notify_clone.clone();
+        async move {
+            let Some(api) = api() else {
+                return;
+            };
+            let toastapi = use_toast();
+            let edit_notify = EditActiveNotify {
+                enable,
+                ..EditActiveNotify::from(&notify)
+            };
+
+            toggling.set(true);
+            let result = api
+                .update_active_notify(&device().id, notify.id, &edit_notify)
+                .await;
+            toggling.set(false);
+
+            match result {
+                Ok(_) => {
+                    let state = if enable { "enabled" } else { "disabled" };
+                    toastapi.success(
+                        format!("{} {state}", notify.name),
+                        ToastOptions::new().duration(Duration::from_secs(5)),
+                    );
+                    on_changed.call(());
+                }
+                Err(e) => {
+                    toastapi.error(
+                        "Update Failed".to_string(),
+                        ToastOptions::new()
+                            .description(format!("{e}"))
+                            .duration(Duration::from_secs(10)),
+                    );
+                }
+            }
+        }
+    };
+
+    let notify_clone = notify.clone();
+    let edit = move |_| notify_ctx.open_edit(&notify_clone);
+    let notify_id = notify.id.to_string();
+    let prompt_delete = move |_| delete_ctx.prompt_delete(&notify_id);
+
     rsx! {
         div { class: "border rounded-lg p-4 relative",
             div { class: "absolute top-2 right-2 flex space-x-2",
-                Button { variant: ButtonVariant::Ghost, "Edit(TODO)" }
-                Button { variant: ButtonVariant::Ghost, "Delete(TODO)" }
+                Button { variant: ButtonVariant::Ghost, onclick: edit,
+                    Icon { icon: fa_solid_icons::FaPen }
+                }
+                Button { variant: ButtonVariant::Ghost, onclick: prompt_delete,
+                    Icon { icon: fa_solid_icons::FaTrash }
+                }
             }
-            div { class: "grid grid-cols-[auto_auto] gap-2",
+            div { class: "grid grid-cols-[auto_auto] gap-2 items-center",
                 div { class: "font-semibold", "ID:" }
                 div { {notify.id.to_string()} }
                 div { class: "font-semibold", "Name:" }
@@ -1161,36 +3500,259 @@ fn ActiveNotifyCard(notify: ActiveNotify) -> Element {
                 div { class: "font-semibold", "Type:" }
                 div { {notify.kind.clone()} }
                 div { class: "font-semibold", "Enabled:" }
-                div { "{notify.enable}" }
-                div { class: "font-semibold", "To:" }
+                Switch {
+                    checked: Some(notify.enable),
+                    disabled: toggling(),
+                    on_checked_change: toggle_enable,
+                    aria_label: "Enable {notify.name}",
+                }
+                div { class: "font-semibold", "{notify_target(&notify.kind).0}:" }
                 div { {notify.setting.to.clone()} }
                 div { class: "font-semibold", "Message:" }
                 div { {notify.setting.message.clone().unwrap_or_default()} }
                 div { class: "font-semibold", "Created:" }
-                div { {notify.create_time.clone()} }
+                div {
+                    TimeLabel { time: notify.create_time }
+                }
             }
         }
     }
 }
 
 #[component]
-fn ActiveNotifySection(notifies: Vec<ActiveNotify>) -> Element {
-    if notifies.is_empty() {
-        rsx! {
-            div {
-                p { "No notifications configured" }
-                Button { "Add(TODO)" }
+fn ActiveNotifySection(
+    device: ReadSignal<Device>,
+    notifies: Vec<ActiveNotify>,
+    on_changed: EventHandler<()>,
+) -> Element {
+    let mut notify_ctx = use_store(|| NotifyCtx {
+        is_open: false,
+        editing: None,
+        form: NotifyForm::default(),
+    });
+    let delete_ctx = use_store(|| DeleteCtx {
+        is_open: false,
+        target: String::new(),
+    });
+    let form_errors = use_memo(move || notify_ctx.form().read().to_edit_notify().err());
+
+    let api = use_api();
+    let on_submit = move |_| async move {
+        let Some(api) = api() else {
+            return;
+        };
+        let toastapi = use_toast();
+        let edit_notify = match notify_ctx.form().read().to_edit_notify() {
+            Ok(edit_notify) => edit_notify,
+            Err(errors) => {
+                toastapi.error(
+                    "Invalid notification".to_string(),
+                    ToastOptions::new()
+                        .description(errors.join(", "))
+                        .duration(Duration::from_secs(10)),
+                );
+                return;
+            }
+        };
+
+        let editing = notify_ctx.editing().cloned();
+        let result = match editing {
+            Some(id) => api.update_active_notify(&device().id, id, &edit_notify).await,
+            None => api.create_active_notify(&device().id, &edit_notify).await,
+        };
+
+        match result {
+            Ok(_) => {
+                let action = if editing.is_some() { "Update" } else { "Add" };
+                toastapi.success(
+                    format!("{action} notification '{}' success", edit_notify.name),
+                    ToastOptions::new().duration(Duration::from_secs(5)),
+                );
+                notify_ctx.is_open().set(false);
+                on_changed.call(());
+            }
+            Err(e) => {
+                toastapi.error(
+                    "Save notification Failed".to_string(),
+                    ToastOptions::new()
+                        .description(format!("{e}"))
+                        .duration(Duration::from_secs(10)),
+                );
             }
         }
-    } else {
-        rsx! {
-            div {
-                div { class: "flex justify-end mb-2",
-                    Button { "Add(TODO)" }
+    };
+
+    let on_delete_confirm = move |_| async move {
+        let Some(api) = api() else {
+            return;
+        };
+        let target = delete_ctx.target().take();
+        delete_ctx.is_open().set(false);
+        let Ok(notify_id) = target.parse::<i32>() else {
+            return;
+        };
+        let toastapi = use_toast();
+
+        match api.delete_active_notify(&device().id, notify_id).await {
+            Ok(_) => {
+                toastapi.success(
+                    format!("Delete notification {notify_id} success"),
+                    ToastOptions::new().duration(Duration::from_secs(5)),
+                );
+                on_changed.call(());
+            }
+            Err(e) => {
+                toastapi.error(
+                    "Delete notification Failed".to_string(),
+                    ToastOptions::new()
+                        .description(format!("{e}"))
+                        .duration(Duration::from_secs(10)),
+                );
+            }
+        }
+    };
+
+    let kind = notify_ctx.form().read().kind.clone();
+    let (target_label, target_placeholder) = notify_target(&kind);
+    let known_kind = NOTIFY_KINDS.iter().any(|(k, _)| *k == kind);
+
+    let notify_dialog = rsx! {
+        DialogRoot {
+            open: *notify_ctx.is_open().read(),
+            on_open_change: move |v| notify_ctx.is_open().set(v),
+            DialogContent {
+                button {
+                    class: "dialog-close",
+                    r#type: "button",
+                    aria_label: "Close",
+                    tabindex: if *notify_ctx.is_open().read() { "0" } else { "-1" },
+                    onclick: move |_| notify_ctx.is_open().set(false),
+                    "×"
+                }
+                DialogTitle {
+                    if notify_ctx.editing().read().is_some() {
+                        "Edit Notification"
+                    } else {
+                        "Add Notification"
+                    }
                 }
+                DialogDescription {
+                    div { class: "flex flex-col gap-4",
+                        Label { html_for: "notify_name", "Name" }
+                        Input {
+                            id: "notify_name",
+                            value: notify_ctx.form().read().name.clone(),
+                            oninput: move |e: FormEvent| notify_ctx.form().write().name = e.value(),
+                        }
+
+                        Label { html_for: "notify_type", "Type" }
+                        if known_kind {
+                            RadioGroup {
+                                id: "notify_type",
+                                value: kind.clone(),
+                                horizontal: true,
+                                on_value_change: move |v: String| notify_ctx.form().write().kind = v,
+                                for (i , (value , label)) in NOTIFY_KINDS.into_iter().enumerate() {
+                                    RadioItem { index: i, value, {label} }
+                                }
+                            }
+                        } else {
+                            p { id: "notify_type", {kind.clone()} }
+                        }
+
+                        Label { html_for: "notify_to", {target_label} }
+                        Input {
+                            id: "notify_to",
+                            r#type: if kind == "email" { "email" } else if kind == "webhook" { "url" } else { "text" },
+                            placeholder: target_placeholder,
+                            value: notify_ctx.form().read().to.clone(),
+                            oninput: move |e: FormEvent| notify_ctx.form().write().to = e.value(),
+                        }
+
+                        Label { html_for: "notify_message", "Message" }
+                        Textarea {
+                            id: "notify_message",
+                            placeholder: "Device is offline",
+                            value: notify_ctx.form().read().message.clone(),
+                            oninput: move |e: FormEvent| notify_ctx.form().write().message = e.value(),
+                        }
+
+                        div { class: "flex items-center gap-4",
+                            Label { html_for: "notify_enable", "Enabled" }
+                            Switch {
+                                id: "notify_enable",
+                                checked: Some(notify_ctx.form().read().enable),
+                                on_checked_change: move |v| notify_ctx.form().write().enable = v,
+                            }
+                        }
+
+                        if let Some(errors) = form_errors() {
+                            ul { class: "text-red-500 text-sm",
+                                for error in errors {
+                                    li { {error} }
+                                }
+                            }
+                        }
+
+                        Button {
+                            r#type: "submit",
+                            disabled: form_errors().is_some(),
+                            onclick: on_submit,
+                            "Submit"
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    let delete_dialog = rsx! {
+        DialogRoot {
+            open: *delete_ctx.is_open().read(),
+            on_open_change: move |v| delete_ctx.is_open().set(v),
+            DialogContent {
+                DialogTitle { "Delete Confirm" }
+                DialogDescription {
+                    div { class: "flex flex-col gap-4",
+                        "Delete notification {delete_ctx.target()}"
+                        div { class: "flex flex-row-reverse gap-4",
+                            Button {
+                                variant: ButtonVariant::Destructive,
+                                onclick: on_delete_confirm,
+                                "Yes"
+                            }
+                            Button {
+                                variant: ButtonVariant::Primary,
+                                onclick: move |_| delete_ctx.is_open().set(false),
+                                "NO"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    rsx! {
+        {notify_dialog}
+        {delete_dialog}
+        div {
+            div { class: "flex justify-end mb-2",
+                Button { onclick: move |_| notify_ctx.open_new(), "Add" }
+            }
+            if notifies.is_empty() {
+                p { "No notifications configured" }
+            } else {
                 div { class: "grid grid-cols-1 gap-4",
                     for notify in notifies {
-                        ActiveNotifyCard { notify: notify.clone() }
+                        ActiveNotifyCard {
+                            key: "{notify.id}",
+                            device,
+                            notify: notify.clone(),
+                            notify_ctx,
+                            delete_ctx,
+                            on_changed,
+                        }
                     }
                 }
             }
@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{use_toast, ToastOptions};
+
+use crate::components::{
+    button::{Button, ButtonVariant},
+    card::{Card, CardContent, CardDescription, CardHeader, CardTitle},
+    input::Input,
+    label::Label,
+    radio_group::{RadioGroup, RadioItem},
+    switch::Switch,
+};
+use crate::models::Preferences;
+use crate::timestamp::{is_valid_time_format, use_now, TimeZoneChoice, Timestamp, TIME_FORMATS};
+use crate::units::UnitSystem;
+
+#[component]
+pub fn SettingsView() -> Element {
+    let mut preferences = use_context::<Signal<Preferences>>();
+    let now = use_now();
+    // Texts as typed; the preference only changes when they are valid.
+    let mut zone_text = use_signal(|| preferences.peek().time_zone.to_string());
+    let mut format_text = use_signal(|| preferences.peek().time_format.clone());
+    let mut stale_text = use_signal(|| (preferences.peek().stale_after_secs / 60).to_string());
+
+    let zone_valid = use_memo(move || TimeZoneChoice::parse(&zone_text()).is_some());
+    let format_valid = use_memo(move || is_valid_time_format(&format_text()));
+    let stale_valid = use_memo(move || stale_text().trim().parse::<u64>().is_ok_and(|m| m > 0));
+
+    let mut set_format = move |format: String| {
+        if is_valid_time_format(&format) {
+            preferences.write().time_format = format.clone();
+        }
+        format_text.set(format);
+    };
+
+    let toggle_notifications = move |on: bool| async move {
+        if !on {
+            preferences.write().browser_notifications = false;
+            return;
+        }
+        let mut eval = document::eval(
+            r#"
+            if (!("Notification" in window)) {
+                dioxus.send("unsupported");
+            } else {
+                Notification.requestPermission().then((p) => dioxus.send(p));
+            }
+            "#,
+        );
+        match eval.recv::<String>().await.as_deref() {
+            Ok("granted") => preferences.write().browser_notifications = true,
+            result => {
+                let reason = match result {
+                    Ok("unsupported") => "This browser does not support notifications".to_string(),
+                    Ok(_) => "Permission was not granted".to_string(),
+                    Err(e) => format!("{e}"),
+                };
+                use_toast().error(
+                    "Notifications unavailable".to_string(),
+                    ToastOptions::new()
+                        .description(reason)
+                        .duration(Duration::from_secs(10)),
+                );
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "flex flex-col gap-4 py-4",
+            Card {
+                CardHeader {
+                    CardTitle { "Units" }
+                    CardDescription {
+                        "Sensors with a unit attribute are converted to this system."
+                    }
+                }
+                CardContent {
+                    div { class: "flex flex-col gap-2",
+                        Label { html_for: "unit_system", "Unit system" }
+                        RadioGroup {
+                            id: "unit_system",
+                            value: preferences().unit_system.as_str().to_string(),
+                            on_value_change: move |v: String| {
+                                if let Some(system) = UnitSystem::from_name(&v) {
+                                    preferences.write().unit_system = system;
+                                }
+                            },
+                            for (i , system) in UnitSystem::ALL.into_iter().enumerate() {
+                                RadioItem { index: i, value: system.as_str().to_string(), "{system}" }
+                            }
+                        }
+                    }
+                }
+            }
+            Card {
+                CardHeader {
+                    CardTitle { "Time" }
+                    CardDescription {
+                        {format!("Now: {}", preferences().format_time(&Timestamp(now())))}
+                    }
+                }
+                CardContent {
+                    div { class: "flex flex-col gap-4",
+                        Label { html_for: "time_zone", "Time zone" }
+                        Input {
+                            id: "time_zone",
+                            placeholder: "local, UTC or +08:00",
+                            value: zone_text(),
+                            oninput: move |e: FormEvent| {
+                                if let Some(zone) = TimeZoneChoice::parse(&e.value()) {
+                                    preferences.write().time_zone = zone;
+                                }
+                                zone_text.set(e.value());
+                            },
+                        }
+                        if !zone_valid() {
+                            p { class: "text-sm text-red-500",
+                                "Use local, UTC or an offset such as +08:00"
+                            }
+                        }
+
+                        Label { html_for: "time_format", "Format" }
+                        div { class: "flex flex-wrap gap-2",
+                            for (format , example) in TIME_FORMATS {
+                                Button {
+                                    variant: if format_text() == format { ButtonVariant::Primary } else { ButtonVariant::Outline },
+                                    onclick: move |_| set_format(format.to_string()),
+                                    "{example}"
+                                }
+                            }
+                        }
+                        Input {
+                            id: "time_format",
+                            placeholder: "%Y-%m-%d %H:%M:%S",
+                            value: format_text(),
+                            oninput: move |e: FormEvent| set_format(e.value()),
+                        }
+                        if !format_valid() {
+                            p { class: "text-sm text-red-500", "Not a valid strftime pattern" }
+                        }
+
+                        Label { html_for: "stale_after", "Stale after (minutes)" }
+                        Input {
+                            id: "stale_after",
+                            r#type: "number",
+                            min: "1",
+                            value: stale_text(),
+                            oninput: move |e: FormEvent| {
+                                if let Ok(minutes @ 1..) = e.value().trim().parse::<u64>() {
+                                    preferences.write().stale_after_secs = minutes * 60;
+                                }
+                                stale_text.set(e.value());
+                            },
+                        }
+                        if stale_valid() {
+                            p { class: "text-sm text-gray-500",
+                                "Devices with an enabled monitor use its period instead."
+                            }
+                        } else {
+                            p { class: "text-sm text-red-500", "Enter a whole number of minutes" }
+                        }
+                    }
+                }
+            }
+            Card {
+                CardHeader {
+                    CardTitle { "Alerts" }
+                    CardDescription {
+                        "Sensors raise alerts from their warn_min, warn_max, crit_min, crit_max, warn_eq and crit_eq attributes."
+                    }
+                }
+                CardContent {
+                    div { class: "flex items-center gap-4",
+                        Switch {
+                            id: "browser_notifications",
+                            checked: preferences().browser_notifications,
+                            on_checked_change: toggle_notifications,
+                        }
+                        Label { html_for: "browser_notifications", "Browser notifications" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,665 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_std::task::sleep;
+use chrono::Utc;
+use dioxus::html::{FileData, HasFileData};
+use dioxus::prelude::*;
+use dioxus_free_icons::icons::fa_solid_icons;
+use dioxus_free_icons::Icon;
+use dioxus_primitives::toast::{use_toast, ToastOptions};
+use futures::stream::{self, StreamExt};
+
+use crate::api::{use_api, ApiClient, ApiError, ApiResult};
+use crate::components::button::{Button, ButtonVariant};
+use crate::components::card::{Card, CardContent, CardDescription, CardHeader, CardTitle};
+use crate::components::input::Input;
+use crate::components::label::Label;
+use crate::components::radio_group::{RadioGroup, RadioItem};
+use crate::decode::{decode_sensor, DecodedValue};
+use crate::models::{Device, EditRawData, Preferences, RawData, RawDataQuery, Sensor};
+use crate::snapshot_cache::{
+    detect_mime, load_snapshot, mime_extension, use_snapshot_cache, CachedSnapshot,
+};
+use crate::timestamp::Timestamp;
+
+use super::sensor::{datetime_input_to_iso, ApiErrorCard};
+
+/// Snapshots downloaded at the same time while filling the gallery.
+const SNAPSHOT_CONCURRENCY: usize = 4;
+/// Most snapshots shown at once, so the gallery fits in the snapshot cache.
+const MAX_FRAMES: u32 = 200;
+
+/// Timelapse speeds and their labels; 1x shows two frames per second.
+const TIMELAPSE_SPEEDS: [(&str, &str); 5] = [
+    ("0.5", "0.5x"),
+    ("1", "1x"),
+    ("2", "2x"),
+    ("4", "4x"),
+    ("8", "8x"),
+];
+const TIMELAPSE_FRAME_MS: f64 = 500.0;
+
+const MIN_ZOOM: f64 = 1.0;
+const MAX_ZOOM: f64 = 8.0;
+
+/// One snapshot in the history of a sensor.
+#[derive(Clone, PartialEq, Debug)]
+struct SnapshotFrame {
+    id: String,
+    time: Option<Timestamp>,
+}
+
+/// Snapshot references in the raw data, oldest first; other values are skipped.
+fn snapshot_frames(rows: &[RawData], sensor: &Sensor) -> Vec<SnapshotFrame> {
+    let mut frames: Vec<SnapshotFrame> = rows
+        .iter()
+        .filter_map(|row| {
+            let id = decode_sensor(sensor, &row.value).ok()?.snapshot_id()?.to_string();
+            Some(SnapshotFrame { id, time: row.time })
+        })
+        .collect();
+    frames.sort_by_key(|f| f.time);
+    frames
+}
+
+/// Thumbnails, lightbox and timelapse of the snapshots a sensor reported in a time range.
+#[component]
+pub fn SnapshotGallery(device: ReadSignal<Device>, sensor: ReadSignal<Sensor>) -> Element {
+    let api = use_api();
+    let preferences = use_context::<Signal<Preferences>>();
+    let mut start = use_signal(String::new);
+    let mut end = use_signal(String::new);
+    let mut limit = use_signal(|| "50".to_string());
+    let mut query = use_signal(|| None::<RawDataQuery>);
+    let mut snapshot_cache = use_snapshot_cache();
+    // Loaded images by snapshot id, whose URLs the gallery holds in the snapshot cache.
+    let mut images = use_signal(HashMap::<String, ApiResult<CachedSnapshot>>::new);
+    let mut lightbox = use_signal(|| None::<usize>);
+
+    let mut release_images = move || {
+        let mut cache = snapshot_cache.write();
+        for image in images.write().drain().filter_map(|(_, image)| image.ok()) {
+            cache.release(&image.url);
+        }
+    };
+    use_drop(move || {
+        if let (Ok(mut images), Ok(mut cache)) = (images.try_write(), snapshot_cache.try_write()) {
+            for image in images.drain().filter_map(|(_, image)| image.ok()) {
+                cache.release(&image.url);
+            }
+        }
+    });
+
+    let mut frames: Resource<ApiResult<Vec<SnapshotFrame>>> = use_resource(move || async move {
+        let Some(query) = query() else {
+            return Ok(Vec::new());
+        };
+        let api = api().ok_or(ApiError::NotConfigured)?;
+        let rows = api.sensor_rawdata(&device().id, &sensor().id, &query).await?;
+        Ok(snapshot_frames(&rows, &sensor()))
+    });
+
+    // Downloads the snapshots of the current frames that are not loaded yet.
+    use_effect(move || {
+        let Some(Ok(frames)) = &*frames.read() else {
+            return;
+        };
+        let Some(api) = api() else {
+            return;
+        };
+        let missing: Vec<String> = frames
+            .iter()
+            .map(|f| f.id.clone())
+            .filter(|id| !images.peek().contains_key(id))
+            .collect();
+        let device_id = device.peek().id.clone();
+        let sensor_id = sensor.peek().id.clone();
+        spawn(async move {
+            let mut loads = stream::iter(missing)
+                .map(move |id| {
+                    let api = api.clone();
+                    let (device_id, sensor_id) = (device_id.clone(), sensor_id.clone());
+                    async move {
+                        let image =
+                            load_snapshot(&api, snapshot_cache, &device_id, &sensor_id, &id).await;
+                        (id, image)
+                    }
+                })
+                .buffer_unordered(SNAPSHOT_CONCURRENCY);
+            while let Some((id, image)) = loads.next().await {
+                if let Some(Ok(old)) = images.write().insert(id, image) {
+                    snapshot_cache.write().release(&old.url);
+                }
+            }
+        });
+    });
+
+    let on_query = move |_| {
+        lightbox.set(None);
+        release_images();
+        let zone = preferences().time_zone;
+        query.set(Some(RawDataQuery {
+            start: datetime_input_to_iso(&start(), zone),
+            end: datetime_input_to_iso(&end(), zone),
+            limit: Some(limit().trim().parse().unwrap_or(MAX_FRAMES).clamp(1, MAX_FRAMES)),
+        }));
+    };
+
+    let content = if query().is_none() {
+        rsx! {
+            p { "Pick a time range and press Query." }
+        }
+    } else if let Some(response) = &*frames.read() {
+        match response {
+            Ok(frames) if frames.is_empty() => rsx! {
+                p { "No snapshots in this range" }
+            },
+            Ok(frames) => rsx! {
+                Timelapse { frames: frames.clone(), images: images() }
+                div { class: "grid grid-cols-2 md:grid-cols-4 lg:grid-cols-6 gap-2 mt-4",
+                    for (i , frame) in frames.iter().enumerate() {
+                        button {
+                            key: "{frame.id}",
+                            class: "flex flex-col gap-1 text-left",
+                            r#type: "button",
+                            onclick: move |_| lightbox.set(Some(i)),
+                            SnapshotImage {
+                                image: images.read().get(&frame.id).cloned(),
+                                class: "w-full h-24 object-cover rounded",
+                            }
+                            span { class: "text-xs text-gray-500 truncate",
+                                {frame.time.map(|t| preferences().format_time(&t)).unwrap_or_default()}
+                            }
+                        }
+                    }
+                }
+                if let Some(index) = lightbox() {
+                    Lightbox {
+                        frames: frames.clone(),
+                        images: images(),
+                        index,
+                        file_prefix: format!("{}-{}", device().id, sensor().id),
+                        on_select: move |i| lightbox.set(Some(i)),
+                        on_close: move |_| lightbox.set(None),
+                    }
+                }
+            },
+            Err(error) => rsx! {
+                ApiErrorCard {
+                    error: error.clone(),
+                    on_retry: move |_| frames.restart(),
+                }
+            },
+        }
+    } else {
+        rsx! {
+            p { "Loading" }
+        }
+    };
+
+    rsx! {
+        Card { class: "mt-8",
+            CardHeader {
+                CardTitle { "Snapshots" }
+                CardDescription {
+                    "Snapshots reported by this sensor. Start and end use the time zone from Settings ({preferences().time_zone})."
+                }
+            }
+            CardContent {
+                div { class: "flex flex-wrap gap-4 items-end mb-4",
+                    div { class: "flex flex-col gap-2",
+                        Label { html_for: "snapshot_start", "Start" }
+                        Input {
+                            id: "snapshot_start",
+                            r#type: "datetime-local",
+                            value: start(),
+                            oninput: move |e: FormEvent| start.set(e.value()),
+                        }
+                    }
+                    div { class: "flex flex-col gap-2",
+                        Label { html_for: "snapshot_end", "End" }
+                        Input {
+                            id: "snapshot_end",
+                            r#type: "datetime-local",
+                            value: end(),
+                            oninput: move |e: FormEvent| end.set(e.value()),
+                        }
+                    }
+                    div { class: "flex flex-col gap-2",
+                        Label { html_for: "snapshot_limit", "Limit" }
+                        Input {
+                            id: "snapshot_limit",
+                            r#type: "number",
+                            min: "1",
+                            max: "{MAX_FRAMES}",
+                            value: limit(),
+                            oninput: move |e: FormEvent| limit.set(e.value()),
+                        }
+                    }
+                    Button { onclick: on_query, "Query" }
+                }
+                {content}
+            }
+        }
+    }
+}
+
+/// A loaded snapshot, a placeholder while it loads, or why it failed.
+#[component]
+fn SnapshotImage(image: Option<ApiResult<CachedSnapshot>>, class: String) -> Element {
+    match image {
+        Some(Ok(snapshot)) => rsx! {
+            img { class, src: snapshot.url }
+        },
+        Some(Err(error)) => rsx! {
+            div { class: "{class} flex items-center justify-center text-xs text-red-500 border",
+                title: "{error}",
+                {error.summary()}
+            }
+        },
+        None => rsx! {
+            div { class: "{class} flex items-center justify-center text-xs text-gray-500 border",
+                "Loading..."
+            }
+        },
+    }
+}
+
+/// Full-screen view of one snapshot, with wheel zoom, drag to pan and download.
+#[component]
+fn Lightbox(
+    frames: Vec<SnapshotFrame>,
+    images: HashMap<String, ApiResult<CachedSnapshot>>,
+    index: usize,
+    /// Start of the downloaded file name, followed by the snapshot id.
+    file_prefix: String,
+    on_select: EventHandler<usize>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let preferences = use_context::<Signal<Preferences>>();
+    let mut zoom = use_signal(|| MIN_ZOOM);
+    let mut offset = use_signal(|| (0.0, 0.0));
+    let mut drag = use_signal(|| None::<(f64, f64)>);
+
+    let Some(frame) = frames.get(index).cloned() else {
+        return rsx! {};
+    };
+    let image = images.get(&frame.id).cloned();
+    let last = frames.len() - 1;
+    let mut reset_view = move || {
+        zoom.set(MIN_ZOOM);
+        offset.set((0.0, 0.0));
+    };
+    let (x, y) = offset();
+
+    rsx! {
+        div {
+            class: "fixed inset-0 z-50 flex flex-col bg-black/90 text-white",
+            tabindex: "0",
+            onmounted: move |e| async move {
+                _ = e.set_focus(true).await;
+            },
+            onkeydown: move |e: KeyboardEvent| match e.key() {
+                Key::Escape => on_close.call(()),
+                Key::ArrowLeft if index > 0 => {
+                    reset_view();
+                    on_select.call(index - 1);
+                }
+                Key::ArrowRight if index < last => {
+                    reset_view();
+                    on_select.call(index + 1);
+                }
+                _ => {}
+            },
+            div { class: "flex items-center gap-4 p-4",
+                span { class: "flex-1",
+                    "{index + 1} / {frames.len()} · "
+                    {frame.time.map(|t| preferences().format_time(&t)).unwrap_or_default()}
+                }
+                span { "{zoom() * 100.0:.0}%" }
+                Button { variant: ButtonVariant::Ghost, onclick: move |_| reset_view(), "Reset" }
+                if let Some(Ok(snapshot)) = &image {
+                    a {
+                        class: "button",
+                        "data-style": "outline",
+                        href: "{snapshot.url}",
+                        download: "{file_prefix}-{frame.id}.{mime_extension(snapshot.mime)}",
+                        Icon { icon: fa_solid_icons::FaDownload }
+                        " Download"
+                    }
+                }
+                Button { variant: ButtonVariant::Ghost, onclick: move |_| on_close.call(()),
+                    Icon { icon: fa_solid_icons::FaXmark }
+                }
+            }
+            div {
+                class: "relative flex-1 overflow-hidden flex items-center justify-center select-none",
+                onwheel: move |e| {
+                    e.prevent_default();
+                    let factor = if e.delta().strip_units().y < 0.0 { 1.25 } else { 0.8 };
+                    let next = (zoom() * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+                    if next == MIN_ZOOM {
+                        offset.set((0.0, 0.0));
+                    }
+                    zoom.set(next);
+                },
+                onmousedown: move |e| {
+                    e.prevent_default();
+                    let p = e.client_coordinates();
+                    drag.set(Some((p.x - offset().0, p.y - offset().1)));
+                },
+                onmousemove: move |e| {
+                    if let Some((sx, sy)) = drag() {
+                        let p = e.client_coordinates();
+                        offset.set((p.x - sx, p.y - sy));
+                    }
+                },
+                onmouseup: move |_| drag.set(None),
+                onmouseleave: move |_| drag.set(None),
+                match image {
+                    Some(Ok(snapshot)) => rsx! {
+                        img {
+                            class: "max-w-full max-h-full",
+                            style: "transform: translate({x}px, {y}px) scale({zoom()}); cursor: grab;",
+                            draggable: "false",
+                            src: snapshot.url,
+                        }
+                    },
+                    Some(Err(error)) => rsx! {
+                        p { class: "text-red-400", "{error}" }
+                    },
+                    None => rsx! {
+                        p { "Loading..." }
+                    },
+                }
+                if index > 0 {
+                    Button {
+                        class: "absolute left-4",
+                        variant: ButtonVariant::Secondary,
+                        onclick: move |_| {
+                            reset_view();
+                            on_select.call(index - 1);
+                        },
+                        Icon { icon: fa_solid_icons::FaChevronLeft }
+                    }
+                }
+                if index < last {
+                    Button {
+                        class: "absolute right-4",
+                        variant: ButtonVariant::Secondary,
+                        onclick: move |_| {
+                            reset_view();
+                            on_select.call(index + 1);
+                        },
+                        Icon { icon: fa_solid_icons::FaChevronRight }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Plays the snapshots in order, with a position slider and speed control.
+#[component]
+fn Timelapse(
+    frames: Vec<SnapshotFrame>,
+    images: HashMap<String, ApiResult<CachedSnapshot>>,
+) -> Element {
+    let preferences = use_context::<Signal<Preferences>>();
+    let mut position = use_signal(|| 0usize);
+    let mut playing = use_signal(|| false);
+    let mut speed = use_signal(|| "1".to_string());
+    let frame_count = use_memo(use_reactive!(|frames| frames.len()));
+
+    use_future(move || async move {
+        loop {
+            let factor = speed.peek().parse::<f64>().unwrap_or(1.0);
+            sleep(Duration::from_millis((TIMELAPSE_FRAME_MS / factor) as u64)).await;
+            if !playing() {
+                continue;
+            }
+            if position() + 1 < frame_count() {
+                *position.write() += 1;
+            } else {
+                playing.set(false);
+            }
+        }
+    });
+
+    let index = position().min(frames.len().saturating_sub(1));
+    let Some(frame) = frames.get(index) else {
+        return rsx! {};
+    };
+
+    rsx! {
+        div { class: "flex flex-col gap-2",
+            div { class: "flex justify-center bg-black rounded h-64",
+                SnapshotImage {
+                    image: images.get(&frame.id).cloned(),
+                    class: "h-64 object-contain",
+                }
+            }
+            div { class: "flex flex-wrap items-center gap-4",
+                Button {
+                    variant: ButtonVariant::Outline,
+                    onclick: move |_| {
+                        if !playing() && position() + 1 >= frame_count() {
+                            position.set(0);
+                        }
+                        playing.toggle();
+                    },
+                    if playing() {
+                        Icon { icon: fa_solid_icons::FaPause }
+                    } else {
+                        Icon { icon: fa_solid_icons::FaPlay }
+                    }
+                }
+                input {
+                    class: "flex-1",
+                    r#type: "range",
+                    min: "0",
+                    max: "{frames.len() - 1}",
+                    value: "{index}",
+                    aria_label: "Timelapse position",
+                    oninput: move |e: FormEvent| {
+                        if let Ok(i) = e.value().parse() {
+                            position.set(i);
+                        }
+                    },
+                }
+                span { class: "text-sm whitespace-nowrap",
+                    "{index + 1} / {frames.len()} · "
+                    {frame.time.map(|t| preferences().format_time(&t)).unwrap_or_default()}
+                }
+                RadioGroup {
+                    value: speed(),
+                    horizontal: true,
+                    on_value_change: move |v: String| speed.set(v),
+                    aria_label: "Timelapse speed",
+                    for (i , (value , label)) in TIMELAPSE_SPEEDS.into_iter().enumerate() {
+                        RadioItem { index: i, value, {label} }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Step of the file being uploaded.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum UploadStage {
+    Reading,
+    Uploading,
+    Saving,
+}
+
+impl UploadStage {
+    fn label(&self) -> &'static str {
+        match self {
+            UploadStage::Reading => "Reading",
+            UploadStage::Uploading => "Uploading",
+            UploadStage::Saving => "Saving",
+        }
+    }
+}
+
+/// Progress of an upload of several files.
+#[derive(Clone, PartialEq, Debug)]
+struct UploadProgress {
+    done: usize,
+    total: usize,
+    file: String,
+    stage: UploadStage,
+}
+
+/// Uploads one image as a new snapshot of the sensor and records it as the sensor's value.
+async fn upload_file(
+    api: &ApiClient,
+    device_id: &str,
+    sensor_id: &str,
+    file: &FileData,
+    mut progress: Signal<Option<UploadProgress>>,
+) -> Result<(), String> {
+    let mut set_stage = move |stage| {
+        if let Some(p) = progress.write().as_mut() {
+            p.stage = stage;
+        }
+    };
+
+    set_stage(UploadStage::Reading);
+    let bytes = file.read_bytes().await.map_err(|e| format!("{e}"))?;
+    let mime = detect_mime(file.content_type().as_deref(), &bytes);
+    if !mime.starts_with("image/") {
+        return Err("Not a JPEG, PNG, GIF or WebP image".to_string());
+    }
+
+    set_stage(UploadStage::Uploading);
+    let snapshot_id = format!("{}", Utc::now().format("%Y%m%d%H%M%S%3f"));
+    api.upload_snapshot(device_id, sensor_id, &snapshot_id, mime, bytes.to_vec())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    set_stage(UploadStage::Saving);
+    let edit = EditRawData {
+        id: sensor_id.to_string(),
+        value: vec![DecodedValue::Snapshot(snapshot_id).to_string()],
+        time: None,
+    };
+    api.write_rawdata(device_id, &[edit])
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Picker and drop zone that upload local images as new snapshots of a sensor.
+#[component]
+pub fn SnapshotUpload(
+    device_id: ReadSignal<String>,
+    sensor_id: ReadSignal<String>,
+    /// Called after files were uploaded, so the card can show the newest snapshot.
+    on_uploaded: EventHandler<()>,
+) -> Element {
+    let api = use_api();
+    let mut progress = use_signal(|| None::<UploadProgress>);
+    let mut dragging = use_signal(|| false);
+    let input_id = use_memo(move || format!("snapshot_upload_{}", sensor_id()));
+
+    let upload = move |files: Vec<FileData>| async move {
+        if files.is_empty() || progress.peek().is_some() {
+            return;
+        }
+        let toastapi = use_toast();
+        let Some(api) = api() else {
+            toastapi.error(
+                "Upload Failed".to_string(),
+                ToastOptions::new()
+                    .description(ApiError::NotConfigured.summary())
+                    .duration(Duration::from_secs(10)),
+            );
+            return;
+        };
+
+        let total = files.len();
+        let mut failed = Vec::new();
+        for (done, file) in files.iter().enumerate() {
+            progress.set(Some(UploadProgress {
+                done,
+                total,
+                file: file.name(),
+                stage: UploadStage::Reading,
+            }));
+            if let Err(e) = upload_file(&api, &device_id(), &sensor_id(), file, progress).await {
+                failed.push(format!("{}: {e}", file.name()));
+            }
+        }
+        progress.set(None);
+
+        let uploaded = total - failed.len();
+        if uploaded > 0 {
+            on_uploaded.call(());
+            toastapi.success(
+                format!("Uploaded {uploaded} snapshot(s)"),
+                ToastOptions::new().duration(Duration::from_secs(5)),
+            );
+        }
+        if !failed.is_empty() {
+            toastapi.error(
+                format!("{} upload(s) failed", failed.len()),
+                ToastOptions::new()
+                    .description(failed.join("\n"))
+                    .duration(Duration::from_secs(10)),
+            );
+        }
+    };
+
+    let zone_class = if dragging() {
+        "border-blue-500 bg-blue-50"
+    } else {
+        "border-gray-300"
+    };
+
+    rsx! {
+        div {
+            class: "flex flex-col gap-1 p-2 rounded border-2 border-dashed text-sm text-center {zone_class}",
+            ondragover: move |e: DragEvent| {
+                e.prevent_default();
+                dragging.set(true);
+            },
+            ondragleave: move |_| dragging.set(false),
+            ondrop: move |e: DragEvent| async move {
+                e.prevent_default();
+                dragging.set(false);
+                upload(e.files()).await;
+            },
+            // The request gives no byte progress, so this shows the step rather than a percentage.
+            if let Some(p) = progress() {
+                p { class: "flex items-center justify-center gap-2",
+                    Icon { class: "animate-spin", icon: fa_solid_icons::FaSpinner }
+                    span { class: "truncate", "{p.stage.label()} {p.file}" }
+                    span { class: "shrink-0 text-gray-500", "file {p.done + 1} of {p.total}" }
+                }
+            } else {
+                p { class: "flex items-center justify-center gap-2 text-gray-500",
+                    Icon { icon: fa_solid_icons::FaUpload }
+                    "Drop images here or"
+                    label {
+                        class: "cursor-pointer underline",
+                        r#for: "{input_id}",
+                        "choose files"
+                    }
+                }
+                input {
+                    id: "{input_id}",
+                    class: "hidden",
+                    r#type: "file",
+                    accept: "image/*",
+                    multiple: true,
+                    onchange: move |e: FormEvent| async move {
+                        upload(e.files()).await;
+                    },
+                }
+            }
+        }
+    }
+}